@@ -0,0 +1,97 @@
+/*
+ * Wrappers for inline kernel functions so they can be called from Rust
+ */
+
+#include <version.h>
+#if KERNEL_VERSION_MAJOR < 3
+#include <zephyr.h>
+#else
+#include <zephyr/kernel.h>
+#endif
+
+#ifdef CONFIG_SHELL
+#if KERNEL_VERSION_MAJOR < 3
+#include <shell/shell.h>
+#else
+#include <zephyr/shell/shell.h>
+#endif
+#endif
+
+unsigned int rust_irq_lock(void)
+{
+	return irq_lock();
+}
+
+void rust_irq_unlock(unsigned int key)
+{
+	irq_unlock(key);
+}
+
+k_spinlock_key_t rust_k_spin_lock(struct k_spinlock *l)
+{
+	return k_spin_lock(l);
+}
+
+void rust_k_spin_unlock(struct k_spinlock *l, k_spinlock_key_t key)
+{
+	k_spin_unlock(l, key);
+}
+
+/* k_mem_slab_free takes the block itself since 3.4, and a pointer to it before */
+void rust_k_mem_slab_free(struct k_mem_slab *slab, void *mem)
+{
+#if ZEPHYR_VERSION_CODE >= ZEPHYR_VERSION(3, 4, 0)
+	k_mem_slab_free(slab, mem);
+#else
+	k_mem_slab_free(slab, &mem);
+#endif
+}
+
+uint32_t rust_k_mem_slab_num_used_get(struct k_mem_slab *slab)
+{
+	return k_mem_slab_num_used_get(slab);
+}
+
+uint32_t rust_k_mem_slab_num_free_get(struct k_mem_slab *slab)
+{
+	return k_mem_slab_num_free_get(slab);
+}
+
+/* k_oops and k_panic are macros raising an exception */
+FUNC_NORETURN void rust_k_oops(void)
+{
+	k_oops();
+	CODE_UNREACHABLE;
+}
+
+FUNC_NORETURN void rust_k_panic(void)
+{
+	k_panic();
+	CODE_UNREACHABLE;
+}
+
+#ifdef CONFIG_USERSPACE
+/* Partition attributes are arch-specific initializers, so they are set here */
+void rust_k_mem_partition_init(struct k_mem_partition *part, uintptr_t start,
+			       size_t size, int user_access)
+{
+	static const k_mem_partition_attr_t attrs[] = {
+		K_MEM_PARTITION_P_RW_U_RW,
+		K_MEM_PARTITION_P_RW_U_RO,
+		K_MEM_PARTITION_P_RW_U_NA,
+	};
+
+	__ASSERT_NO_MSG(user_access >= 0 && user_access < ARRAY_SIZE(attrs));
+	part->start = start;
+	part->size = size;
+	part->attr = attrs[user_access];
+}
+#endif
+
+#ifdef CONFIG_SHELL
+/* shell_fprintf is variadic */
+void rust_shell_write(const struct shell *sh, const char *data, size_t len)
+{
+	shell_fprintf(sh, SHELL_NORMAL, "%.*s", (int)len, data);
+}
+#endif
@@ -0,0 +1,116 @@
+/*
+ * Zephyr log core backend for zephyr-logger
+ */
+
+#include <version.h>
+#if KERNEL_VERSION_MAJOR < 3
+#include <zephyr.h>
+#else
+#include <zephyr/kernel.h>
+#endif
+
+#ifdef CONFIG_LOG
+#if KERNEL_VERSION_MAJOR < 3
+#include <logging/log.h>
+#include <logging/log_ctrl.h>
+#else
+#include <zephyr/logging/log.h>
+#include <zephyr/logging/log_ctrl.h>
+#endif
+
+LOG_MODULE_REGISTER(rust, CONFIG_RUST_LOG_LEVEL);
+
+#if (KERNEL_VERSION_MAJOR < 3 && !defined(CONFIG_LOG2)) || defined(CONFIG_LOG1)
+#define RUST_LOG1
+#endif
+
+/* The message is formatted on the caller's stack, so it must be copied for deferred logging. Log
+ * v2 and later copy strings themselves.
+ */
+#ifdef RUST_LOG1
+#define RUST_LOG_STR(s) log_strdup(s)
+#else
+#define RUST_LOG_STR(s) (s)
+#endif
+
+/* Minimal logging prints with printk and has no sources, so every record goes through LOG_* */
+int rust_log_source_id(const char *name)
+{
+#ifdef CONFIG_LOG_MODE_MINIMAL
+	return -1;
+#else
+	return log_source_id_get(name);
+#endif
+}
+
+/* Log to the module registered with LOG_MODULE_REGISTER as source_id. Only the Rust logger's
+ * levels apply, not the module's.
+ */
+static void rust_log_write_source(int level, int source_id, const char *msg)
+{
+#if defined(CONFIG_LOG_MODE_MINIMAL)
+	/* Not called, as there are no source IDs */
+#elif defined(RUST_LOG1)
+	struct log_msg_ids src_level = {
+		.level = level,
+		.domain_id = CONFIG_LOG_DOMAIN_ID,
+		.source_id = source_id,
+	};
+
+#if defined(CONFIG_LOG_IMMEDIATE) || defined(CONFIG_LOG_MODE_IMMEDIATE)
+	log_string_sync(src_level, "%s", msg);
+#else
+	log_1("%s", (log_arg_t)log_strdup(msg), src_level);
+#endif
+#else /* Log v2 and later take a pointer to the source's data */
+#if KERNEL_VERSION_MAJOR < 3
+#ifdef CONFIG_LOG_RUNTIME_FILTERING
+	const void *source = &__log_dynamic_start[source_id];
+#else
+	const void *source = &__log_const_start[source_id];
+#endif
+	z_log_msg2_runtime_create(CONFIG_LOG_DOMAIN_ID, source, level, NULL, 0, "%s", msg);
+#else
+#ifdef CONFIG_LOG_RUNTIME_FILTERING
+	const void *source = &TYPE_SECTION_START(log_dynamic)[source_id];
+#else
+	const void *source = &TYPE_SECTION_START(log_const)[source_id];
+#endif
+	z_log_msg_runtime_create(Z_LOG_LOCAL_DOMAIN_ID, source, level, NULL, 0, 0, "%s", msg);
+#endif
+#endif
+}
+
+void rust_log_write(int level, int source_id, const char *msg)
+{
+	if (source_id >= 0) {
+		rust_log_write_source(level, source_id, msg);
+		return;
+	}
+
+	switch (level) {
+	case LOG_LEVEL_ERR:
+		LOG_ERR("%s", RUST_LOG_STR(msg));
+		break;
+	case LOG_LEVEL_WRN:
+		LOG_WRN("%s", RUST_LOG_STR(msg));
+		break;
+	case LOG_LEVEL_INF:
+		LOG_INF("%s", RUST_LOG_STR(msg));
+		break;
+	default:
+		LOG_DBG("%s", RUST_LOG_STR(msg));
+		break;
+	}
+}
+#else
+/* Without the log core, records are dropped */
+int rust_log_source_id(const char *name)
+{
+	return -1;
+}
+
+void rust_log_write(int level, int source_id, const char *msg)
+{
+}
+#endif
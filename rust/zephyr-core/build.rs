@@ -8,6 +8,9 @@ fn main() {
     if kernel_version >= 0x2_05_00 {
         println!("cargo:rustc-cfg=zephyr250");
     }
+    if kernel_version >= 0x2_06_00 {
+        println!("cargo:rustc-cfg=zephyr260");
+    }
     if kernel_version >= 0x2_07_00 {
         println!("cargo:rustc-cfg=zephyr270");
     }
@@ -30,6 +33,58 @@ fn main() {
     if std::env::var("CONFIG_POSIX_CLOCK").expect("CONFIG_POSIX_CLOCK must be set") == "y" {
         println!("cargo:rustc-cfg=clock");
     }
+    // Only exists on Zephyr 3.0+
+    if let Ok(events) = std::env::var("CONFIG_EVENTS") {
+        if events == "y" {
+            println!("cargo:rustc-cfg=events");
+        }
+    }
+    if let Ok(dynamic_objects) = std::env::var("CONFIG_DYNAMIC_OBJECTS") {
+        if dynamic_objects == "y" {
+            println!("cargo:rustc-cfg=dynamic_objects");
+        }
+    }
+    if let Ok(static_kobj_init) = std::env::var("CONFIG_RUST_STATIC_KOBJ_INIT") {
+        if static_kobj_init == "y" {
+            println!("cargo:rustc-cfg=static_kobj_init");
+        }
+    }
+    if let Ok(poll) = std::env::var("CONFIG_POLL") {
+        if poll == "y" {
+            println!("cargo:rustc-cfg=poll");
+        }
+    }
+    if let Ok(runtime_frequency) = std::env::var("CONFIG_TIMER_READS_ITS_FREQUENCY_AT_RUNTIME") {
+        if runtime_frequency == "y" {
+            println!("cargo:rustc-cfg=timer_reads_frequency_at_runtime");
+        }
+    }
+    if let Ok(shell) = std::env::var("CONFIG_SHELL") {
+        if shell == "y" {
+            println!("cargo:rustc-cfg=shell");
+        }
+    }
+    if let Ok(deadline) = std::env::var("CONFIG_SCHED_DEADLINE") {
+        if deadline == "y" {
+            println!("cargo:rustc-cfg=sched_deadline");
+        }
+    }
+    // Something to run .ctors: STATIC_INIT_GNU on 3.7+, C++ before. CPLUSPLUS was renamed to CPP
+    // in 3.3.
+    if ["CONFIG_STATIC_INIT_GNU", "CONFIG_CPLUSPLUS", "CONFIG_CPP"]
+        .iter()
+        .any(|var| std::env::var(var).map_or(false, |val| val == "y"))
+    {
+        println!("cargo:rustc-cfg=ctors");
+    }
+    // x86 aligns stacks to an MMU page when they have guard pages or may be used in user mode
+    if ["CONFIG_HW_STACK_PROTECTION", "CONFIG_USERSPACE"]
+        .iter()
+        .any(|var| std::env::var(var).map_or(false, |val| val == "y"))
+        && std::env::var("CONFIG_X86").map_or(false, |val| val == "y")
+    {
+        println!("cargo:rustc-cfg=kernel_stack_page_align");
+    }
     if let Ok(tls) = std::env::var("CONFIG_THREAD_LOCAL_STORAGE") {
         if tls == "y" {
             println!("cargo:rustc-cfg=tls");
@@ -0,0 +1,168 @@
+use zephyr_sys::raw::{k_condvar, k_mutex, k_objects, k_timeout_t};
+
+use super::NegErr;
+use crate::error::{Error, Result};
+use crate::kobj::*;
+use crate::mutex::{MutexGuard, MutexSyscalls};
+use crate::time::Timeout;
+
+// Declare the Zephyr struct to be a kernel object
+unsafe impl KObj for k_condvar {
+    const OTYPE: k_objects = zephyr_sys::raw::k_objects_K_OBJ_CONDVAR;
+}
+
+pub use zephyr_sys::raw::k_condvar as KCondvar;
+
+crate::make_static_wrapper!(k_condvar, zephyr_sys::raw::k_condvar);
+
+#[cfg(static_kobj_init)]
+impl global::k_condvar {
+    /// Equivalent of Z_CONDVAR_INITIALIZER, for a static at address `this`
+    pub const unsafe fn initializer(this: *const Self) -> Self {
+        let this = this as *const k_condvar;
+        let mut condvar: k_condvar = core::mem::zeroed();
+        condvar.wait_q = wait_q_init(core::ptr::addr_of!((*this).wait_q));
+        Self::new(condvar)
+    }
+}
+
+/// Raw syscall API
+pub trait CondvarSyscalls {
+    unsafe fn k_condvar_init(condvar: &k_condvar);
+    fn k_condvar_signal(condvar: &k_condvar);
+    fn k_condvar_broadcast(condvar: &k_condvar);
+    unsafe fn k_condvar_wait(
+        condvar: &k_condvar,
+        mutex: &k_mutex,
+        timeout: k_timeout_t,
+    ) -> libc::c_int;
+}
+
+macro_rules! trait_impl {
+    ($context:ident, $context_struct:path) => {
+        impl CondvarSyscalls for $context_struct {
+            unsafe fn k_condvar_init(condvar: &k_condvar) {
+                zephyr_sys::syscalls::$context::k_condvar_init(condvar as *const _ as *mut _);
+            }
+
+            fn k_condvar_signal(condvar: &k_condvar) {
+                unsafe {
+                    zephyr_sys::syscalls::$context::k_condvar_signal(condvar as *const _ as *mut _);
+                }
+            }
+
+            fn k_condvar_broadcast(condvar: &k_condvar) {
+                unsafe {
+                    zephyr_sys::syscalls::$context::k_condvar_broadcast(
+                        condvar as *const _ as *mut _,
+                    );
+                }
+            }
+
+            unsafe fn k_condvar_wait(
+                condvar: &k_condvar,
+                mutex: &k_mutex,
+                timeout: k_timeout_t,
+            ) -> libc::c_int {
+                zephyr_sys::syscalls::$context::k_condvar_wait(
+                    condvar as *const _ as *mut _,
+                    mutex as *const _ as *mut _,
+                    timeout,
+                )
+            }
+        }
+    };
+}
+
+trait_impl!(kernel, crate::context::Kernel);
+trait_impl!(user, crate::context::User);
+trait_impl!(any, crate::context::Any);
+
+/// Whether a timed wait returned because of the timeout
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WaitTimeoutResult(bool);
+
+impl WaitTimeoutResult {
+    pub fn timed_out(&self) -> bool {
+        self.0
+    }
+}
+
+/// Safe API implemented on the condvar struct, used together with `mutex::Mutex`
+///
+/// The wait functions take the guard of a locked mutex, atomically release the mutex while
+/// waiting, and give the guard back once the mutex is locked again. The syscall context is taken
+/// from the guard. If the wait fails, the guard is dropped, unlocking the mutex. As with any
+/// condition variable, wakeups may be spurious, so the condition should be checked in a loop or
+/// with `wait_while`.
+pub trait Condvar {
+    unsafe fn init<C: CondvarSyscalls>(&self);
+    /// Wait with infinite timeout
+    fn wait<'a, T, C: MutexSyscalls + CondvarSyscalls>(
+        &self,
+        guard: MutexGuard<'a, T, C>,
+    ) -> Result<MutexGuard<'a, T, C>>;
+    /// Wait with timeout. The mutex is locked again whether or not the wait timed out.
+    fn wait_timeout<'a, T, C: MutexSyscalls + CondvarSyscalls>(
+        &self,
+        guard: MutexGuard<'a, T, C>,
+        timeout: Timeout,
+    ) -> Result<(MutexGuard<'a, T, C>, WaitTimeoutResult)>;
+    /// Wait with infinite timeout until `condition` returns false
+    fn wait_while<'a, T, C: MutexSyscalls + CondvarSyscalls, F: FnMut(&mut T) -> bool>(
+        &self,
+        guard: MutexGuard<'a, T, C>,
+        condition: F,
+    ) -> Result<MutexGuard<'a, T, C>>;
+    /// Wake one waiting thread
+    fn notify_one<C: CondvarSyscalls>(&self);
+    /// Wake all waiting threads
+    fn notify_all<C: CondvarSyscalls>(&self);
+}
+
+impl Condvar for k_condvar {
+    unsafe fn init<C: CondvarSyscalls>(&self) {
+        C::k_condvar_init(self)
+    }
+
+    fn wait<'a, T, C: MutexSyscalls + CondvarSyscalls>(
+        &self,
+        guard: MutexGuard<'a, T, C>,
+    ) -> Result<MutexGuard<'a, T, C>> {
+        unsafe { C::k_condvar_wait(self, guard.kmutex(), zephyr_sys::raw::K_FOREVER) }.neg_err()?;
+        Ok(guard)
+    }
+
+    fn wait_timeout<'a, T, C: MutexSyscalls + CondvarSyscalls>(
+        &self,
+        guard: MutexGuard<'a, T, C>,
+        timeout: Timeout,
+    ) -> Result<(MutexGuard<'a, T, C>, WaitTimeoutResult)> {
+        let timed_out =
+            match unsafe { C::k_condvar_wait(self, guard.kmutex(), timeout.0) }.neg_err() {
+                Ok(_) => false,
+                Err(Error::Again) => true,
+                Err(e) => return Err(e),
+            };
+        Ok((guard, WaitTimeoutResult(timed_out)))
+    }
+
+    fn wait_while<'a, T, C: MutexSyscalls + CondvarSyscalls, F: FnMut(&mut T) -> bool>(
+        &self,
+        mut guard: MutexGuard<'a, T, C>,
+        mut condition: F,
+    ) -> Result<MutexGuard<'a, T, C>> {
+        while condition(&mut *guard) {
+            guard = self.wait(guard)?;
+        }
+        Ok(guard)
+    }
+
+    fn notify_one<C: CondvarSyscalls>(&self) {
+        C::k_condvar_signal(self)
+    }
+
+    fn notify_all<C: CondvarSyscalls>(&self) {
+        C::k_condvar_broadcast(self)
+    }
+}
@@ -0,0 +1,89 @@
+//! Errors returned by Zephyr APIs
+//!
+//! Zephyr functions report errors as a negative errno. `Error` names the ones the kernel returns,
+//! so callers can match on them without comparing raw numbers. Anything else is kept as
+//! `Error::Other`, so converting to and from an errno is lossless.
+
+use core::fmt;
+
+macro_rules! errors {
+    ($($(#[$meta:meta])* $name:ident = $errno:ident,)*) => {
+        /// Error from a Zephyr API
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub enum Error {
+            $($(#[$meta])* $name,)*
+            /// Any other errno
+            Other(u32),
+        }
+
+        impl Error {
+            /// Convert from a positive errno
+            pub fn from_errno(errno: u32) -> Self {
+                match errno {
+                    $(zephyr_sys::raw::$errno => Error::$name,)*
+                    errno => Error::Other(errno),
+                }
+            }
+
+            /// The positive errno
+            pub fn errno(self) -> u32 {
+                match self {
+                    $(Error::$name => zephyr_sys::raw::$errno,)*
+                    Error::Other(errno) => errno,
+                }
+            }
+        }
+    };
+}
+
+errors! {
+    /// EPERM: not permitted, e.g. unlocking a mutex locked by another thread
+    NotPermitted = EPERM,
+    /// ENOENT: no such entry, e.g. a partition that is not in a memory domain
+    NoEntry = ENOENT,
+    /// EINTR: the wait was interrupted, e.g. a poll was cancelled
+    Interrupted = EINTR,
+    /// EIO: I/O error, or a non-blocking pipe transfer that moved nothing
+    Io = EIO,
+    /// EAGAIN: timed out waiting, or the object was reset while waiting
+    Again = EAGAIN,
+    /// ENOMEM: out of memory
+    NoMemory = ENOMEM,
+    /// EACCES: permission denied
+    AccessDenied = EACCES,
+    /// EFAULT: bad address
+    BadAddress = EFAULT,
+    /// EBUSY: not available and the timeout was `K_NO_WAIT`, or still in use
+    Busy = EBUSY,
+    /// ENODEV: no such device, or a work queue that is not started
+    NoDevice = ENODEV,
+    /// EINVAL: invalid argument or state
+    InvalidArgument = EINVAL,
+    /// ENOSPC: no space left, e.g. a buffer too small for the result
+    NoSpace = ENOSPC,
+    /// EPIPE: the other end of a pipe is gone
+    BrokenPipe = EPIPE,
+    /// EDEADLK: the operation would deadlock
+    Deadlock = EDEADLK,
+    /// ENOMSG: no message, e.g. a message queue is empty or full
+    NoMessage = ENOMSG,
+    /// ENOSYS: not implemented, or disabled in the kernel config
+    NotImplemented = ENOSYS,
+    /// ENOTSUP: not supported
+    NotSupported = ENOTSUP,
+    /// EALREADY: already in progress
+    Already = EALREADY,
+    /// ECANCELED: cancelled
+    Canceled = ECANCELED,
+    /// ETIMEDOUT: timed out
+    TimedOut = ETIMEDOUT,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} (errno {})", self, self.errno())
+    }
+}
+
+/// Result with a Zephyr error
+pub type Result<T> = core::result::Result<T, Error>;
@@ -0,0 +1,310 @@
+use zephyr_sys::raw::{k_event, k_objects, k_timeout_t};
+
+use crate::kobj::*;
+use crate::time::Timeout;
+
+// Declare the Zephyr struct to be a kernel object
+unsafe impl KObj for k_event {
+    const OTYPE: k_objects = zephyr_sys::raw::k_objects_K_OBJ_EVENT;
+}
+
+pub use zephyr_sys::raw::k_event as KEvent;
+
+crate::make_static_wrapper!(k_event, zephyr_sys::raw::k_event);
+
+/// Raw syscall API
+pub trait EventSyscalls {
+    unsafe fn k_event_init(event: &k_event);
+    fn k_event_post(event: &k_event, events: u32);
+    fn k_event_set(event: &k_event, events: u32);
+    /// k_event_set_masked with no events before Zephyr 3.5
+    fn k_event_clear(event: &k_event, events: u32);
+    fn k_event_wait(event: &k_event, events: u32, reset: bool, timeout: k_timeout_t) -> u32;
+    fn k_event_wait_all(event: &k_event, events: u32, reset: bool, timeout: k_timeout_t) -> u32;
+}
+
+macro_rules! trait_impl {
+    ($context:ident, $context_struct:path) => {
+        impl EventSyscalls for $context_struct {
+            unsafe fn k_event_init(event: &k_event) {
+                zephyr_sys::syscalls::$context::k_event_init(event as *const _ as *mut _)
+            }
+
+            // The previous events are returned since Zephyr 3.4. Not needed here.
+            fn k_event_post(event: &k_event, events: u32) {
+                unsafe {
+                    zephyr_sys::syscalls::$context::k_event_post(
+                        event as *const _ as *mut _,
+                        events,
+                    );
+                }
+            }
+
+            fn k_event_set(event: &k_event, events: u32) {
+                unsafe {
+                    zephyr_sys::syscalls::$context::k_event_set(
+                        event as *const _ as *mut _,
+                        events,
+                    );
+                }
+            }
+
+            #[cfg(zephyr350)]
+            fn k_event_clear(event: &k_event, events: u32) {
+                unsafe {
+                    zephyr_sys::syscalls::$context::k_event_clear(
+                        event as *const _ as *mut _,
+                        events,
+                    );
+                }
+            }
+
+            #[cfg(not(zephyr350))]
+            fn k_event_clear(event: &k_event, events: u32) {
+                unsafe {
+                    zephyr_sys::syscalls::$context::k_event_set_masked(
+                        event as *const _ as *mut _,
+                        0,
+                        events,
+                    );
+                }
+            }
+
+            fn k_event_wait(
+                event: &k_event,
+                events: u32,
+                reset: bool,
+                timeout: k_timeout_t,
+            ) -> u32 {
+                unsafe {
+                    zephyr_sys::syscalls::$context::k_event_wait(
+                        event as *const _ as *mut _,
+                        events,
+                        reset,
+                        timeout,
+                    )
+                }
+            }
+
+            fn k_event_wait_all(
+                event: &k_event,
+                events: u32,
+                reset: bool,
+                timeout: k_timeout_t,
+            ) -> u32 {
+                unsafe {
+                    zephyr_sys::syscalls::$context::k_event_wait_all(
+                        event as *const _ as *mut _,
+                        events,
+                        reset,
+                        timeout,
+                    )
+                }
+            }
+        }
+    };
+}
+
+trait_impl!(kernel, crate::context::Kernel);
+trait_impl!(user, crate::context::User);
+trait_impl!(any, crate::context::Any);
+
+/// A set of event bits. Implemented for `u32` and for types declared with `event_flags!`.
+pub trait EventFlags: Copy {
+    fn bits(&self) -> u32;
+    /// Convert from raw bits, dropping any not defined for this type
+    fn from_bits_truncate(bits: u32) -> Self;
+}
+
+impl EventFlags for u32 {
+    fn bits(&self) -> u32 {
+        *self
+    }
+
+    fn from_bits_truncate(bits: u32) -> Self {
+        bits
+    }
+}
+
+/// Declare a typed set of event flags
+///
+/// ```ignore
+/// zephyr::event_flags! {
+///     pub struct NetEvents {
+///         const LINK_UP = 1 << 0;
+///         const LINK_DOWN = 1 << 1;
+///     }
+/// }
+///
+/// EVENTS.post::<Kernel, _>(NetEvents::LINK_UP);
+/// ```
+#[macro_export]
+macro_rules! event_flags {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                $(#[$flag_attr:meta])*
+                const $flag:ident = $value:expr;
+            )*
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+        $vis struct $name(u32);
+
+        #[allow(dead_code)]
+        impl $name {
+            $(
+                $(#[$flag_attr])*
+                pub const $flag: $name = $name($value);
+            )*
+
+            pub const fn empty() -> Self {
+                $name(0)
+            }
+
+            pub const fn all() -> Self {
+                $name(0 $(| $value)*)
+            }
+
+            pub const fn bits(&self) -> u32 {
+                self.0
+            }
+
+            pub const fn from_bits_truncate(bits: u32) -> Self {
+                $name(bits & Self::all().0)
+            }
+
+            pub const fn is_empty(&self) -> bool {
+                self.0 == 0
+            }
+
+            pub const fn contains(&self, other: Self) -> bool {
+                self.0 & other.0 == other.0
+            }
+
+            pub const fn intersects(&self, other: Self) -> bool {
+                self.0 & other.0 != 0
+            }
+        }
+
+        impl $crate::event::EventFlags for $name {
+            fn bits(&self) -> u32 {
+                self.0
+            }
+
+            fn from_bits_truncate(bits: u32) -> Self {
+                $name::from_bits_truncate(bits)
+            }
+        }
+
+        impl ::core::ops::BitOr for $name {
+            type Output = Self;
+
+            fn bitor(self, other: Self) -> Self {
+                $name(self.0 | other.0)
+            }
+        }
+
+        impl ::core::ops::BitOrAssign for $name {
+            fn bitor_assign(&mut self, other: Self) {
+                self.0 |= other.0;
+            }
+        }
+
+        impl ::core::ops::BitAnd for $name {
+            type Output = Self;
+
+            fn bitand(self, other: Self) -> Self {
+                $name(self.0 & other.0)
+            }
+        }
+
+        impl ::core::ops::BitAndAssign for $name {
+            fn bitand_assign(&mut self, other: Self) {
+                self.0 &= other.0;
+            }
+        }
+
+        impl ::core::ops::Not for $name {
+            type Output = Self;
+
+            fn not(self) -> Self {
+                Self::from_bits_truncate(!self.0)
+            }
+        }
+    };
+}
+
+/// Safe API implemented on the event struct
+///
+/// The wait functions take a `reset` flag which clears all events before waiting, so only
+/// events posted after the call can satisfy it.
+pub trait Event {
+    unsafe fn init<C: EventSyscalls>(&self);
+    /// Set the given events, leaving others unchanged, and wake any satisfied waiters
+    fn post<C: EventSyscalls, F: EventFlags>(&self, events: F);
+    /// Replace all events with the given set and wake any satisfied waiters
+    fn set<C: EventSyscalls, F: EventFlags>(&self, events: F);
+    /// Clear the given events, leaving others unchanged
+    fn clear<C: EventSyscalls, F: EventFlags>(&self, events: F);
+    /// Wait for any of the given events. Returns the events that satisfied the wait. None if
+    /// timeout.
+    fn wait_any<C: EventSyscalls, F: EventFlags>(
+        &self,
+        events: F,
+        reset: bool,
+        timeout: Timeout,
+    ) -> Option<F>;
+    /// Wait for all of the given events. Returns the events that satisfied the wait. None if
+    /// timeout.
+    fn wait_all<C: EventSyscalls, F: EventFlags>(
+        &self,
+        events: F,
+        reset: bool,
+        timeout: Timeout,
+    ) -> Option<F>;
+}
+
+impl Event for k_event {
+    unsafe fn init<C: EventSyscalls>(&self) {
+        C::k_event_init(self)
+    }
+
+    fn post<C: EventSyscalls, F: EventFlags>(&self, events: F) {
+        C::k_event_post(self, events.bits())
+    }
+
+    fn set<C: EventSyscalls, F: EventFlags>(&self, events: F) {
+        C::k_event_set(self, events.bits())
+    }
+
+    fn clear<C: EventSyscalls, F: EventFlags>(&self, events: F) {
+        C::k_event_clear(self, events.bits())
+    }
+
+    fn wait_any<C: EventSyscalls, F: EventFlags>(
+        &self,
+        events: F,
+        reset: bool,
+        timeout: Timeout,
+    ) -> Option<F> {
+        match C::k_event_wait(self, events.bits(), reset, timeout.0) {
+            0 => None,
+            matched => Some(F::from_bits_truncate(matched)),
+        }
+    }
+
+    fn wait_all<C: EventSyscalls, F: EventFlags>(
+        &self,
+        events: F,
+        reset: bool,
+        timeout: Timeout,
+    ) -> Option<F> {
+        match C::k_event_wait_all(self, events.bits(), reset, timeout.0) {
+            0 => None,
+            matched => Some(F::from_bits_truncate(matched)),
+        }
+    }
+}
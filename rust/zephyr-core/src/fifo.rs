@@ -0,0 +1,292 @@
+//! Fifo and lifo channels passing ownership of boxed values between threads
+//!
+//! k_fifo and k_lifo are thin wrappers around k_queue, which is the actual kernel object. Items
+//! are passed by pointer, so the value is never copied. The kernel needs the first word of each
+//! item for its list link, so items are `QueueBox<T>`, a box that reserves that word in the same
+//! allocation as the value.
+
+use alloc::boxed::Box;
+use core::fmt;
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
+use core::ptr;
+
+use libc::{c_int, c_void};
+use zephyr_sys::raw::{k_objects, k_queue, k_timeout_t};
+
+use super::NegErr;
+use crate::error::Result;
+use crate::kobj::*;
+use crate::time::Timeout;
+
+// Declare the Zephyr struct to be a kernel object
+unsafe impl KObj for k_queue {
+    const OTYPE: k_objects = zephyr_sys::raw::k_objects_K_OBJ_QUEUE;
+}
+
+pub use zephyr_sys::raw::k_queue as KQueue;
+
+crate::make_static_wrapper!(k_queue, zephyr_sys::raw::k_queue);
+
+/// Raw syscall API
+///
+/// Appending and prepending differ by context. Kernel mode links the item in place using its
+/// reserved first word. User mode cannot hand the kernel a pointer to user memory to write to,
+/// so the kernel allocates a separate node from the calling thread's resource pool. This fails
+/// with ENOMEM if the thread has no resource pool assigned. The runtime-detect context always
+/// allocates.
+pub trait QueueSyscalls {
+    unsafe fn k_queue_init(queue: &k_queue);
+    unsafe fn k_queue_append(queue: &k_queue, data: *mut c_void) -> c_int;
+    unsafe fn k_queue_prepend(queue: &k_queue, data: *mut c_void) -> c_int;
+    fn k_queue_get(queue: &k_queue, timeout: k_timeout_t) -> *mut c_void;
+    fn k_queue_is_empty(queue: &k_queue) -> c_int;
+    fn k_queue_cancel_wait(queue: &k_queue);
+}
+
+macro_rules! trait_impl {
+    ($context:ident, $context_struct:path, link) => {
+        trait_impl!(@impl $context, $context_struct,
+            append(queue, data) {
+                zephyr_sys::raw::k_queue_append(queue as *const _ as *mut _, data);
+                0
+            }
+            prepend(queue, data) {
+                zephyr_sys::raw::k_queue_prepend(queue as *const _ as *mut _, data);
+                0
+            }
+        );
+    };
+    ($context:ident, $context_struct:path, alloc) => {
+        trait_impl!(@impl $context, $context_struct,
+            append(queue, data) {
+                zephyr_sys::syscalls::$context::k_queue_alloc_append(
+                    queue as *const _ as *mut _,
+                    data,
+                )
+            }
+            prepend(queue, data) {
+                zephyr_sys::syscalls::$context::k_queue_alloc_prepend(
+                    queue as *const _ as *mut _,
+                    data,
+                )
+            }
+        );
+    };
+    (@impl $context:ident, $context_struct:path,
+        append($aq:ident, $ad:ident) $append:block
+        prepend($pq:ident, $pd:ident) $prepend:block
+    ) => {
+        impl QueueSyscalls for $context_struct {
+            unsafe fn k_queue_init(queue: &k_queue) {
+                zephyr_sys::syscalls::$context::k_queue_init(queue as *const _ as *mut _)
+            }
+
+            unsafe fn k_queue_append($aq: &k_queue, $ad: *mut c_void) -> c_int $append
+
+            unsafe fn k_queue_prepend($pq: &k_queue, $pd: *mut c_void) -> c_int $prepend
+
+            fn k_queue_get(queue: &k_queue, timeout: k_timeout_t) -> *mut c_void {
+                unsafe {
+                    zephyr_sys::syscalls::$context::k_queue_get(queue as *const _ as *mut _, timeout)
+                }
+            }
+
+            fn k_queue_is_empty(queue: &k_queue) -> c_int {
+                unsafe {
+                    zephyr_sys::syscalls::$context::k_queue_is_empty(queue as *const _ as *mut _)
+                }
+            }
+
+            fn k_queue_cancel_wait(queue: &k_queue) {
+                unsafe {
+                    zephyr_sys::syscalls::$context::k_queue_cancel_wait(queue as *const _ as *mut _)
+                }
+            }
+        }
+    };
+}
+
+trait_impl!(kernel, crate::context::Kernel, link);
+trait_impl!(user, crate::context::User, alloc);
+trait_impl!(any, crate::context::Any, alloc);
+
+/// Initialization of a statically allocated queue
+pub trait RawQueue {
+    unsafe fn init<C: QueueSyscalls>(&self);
+}
+
+impl RawQueue for KQueue {
+    unsafe fn init<C: QueueSyscalls>(&self) {
+        C::k_queue_init(self)
+    }
+}
+
+/// What the kernel links into the queue. The first word is reserved for the kernel.
+#[repr(C)]
+struct Node<T> {
+    _reserved: *mut c_void,
+    item: T,
+}
+
+/// Heap allocated value that can be put in a `Fifo` or `Lifo`. The allocation has room for the
+/// kernel's list link ahead of the value.
+pub struct QueueBox<T>(Box<Node<T>>);
+
+impl<T> QueueBox<T> {
+    pub fn new(value: T) -> Self {
+        QueueBox(Box::new(Node {
+            _reserved: ptr::null_mut(),
+            item: value,
+        }))
+    }
+
+    /// Move the value out, freeing the allocation
+    pub fn into_inner(b: Self) -> T {
+        b.0.item
+    }
+}
+
+impl<T> From<T> for QueueBox<T> {
+    fn from(value: T) -> Self {
+        QueueBox::new(value)
+    }
+}
+
+impl<T> Deref for QueueBox<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0.item
+    }
+}
+
+impl<T> DerefMut for QueueBox<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0.item
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for QueueBox<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: PartialEq> PartialEq for QueueBox<T> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+unsafe fn queue_insert<C: QueueSyscalls, T>(
+    queue: &KQueue,
+    item: QueueBox<T>,
+    prepend: bool,
+) -> Result<()> {
+    let node = Box::into_raw(item.0);
+    let ret = if prepend {
+        C::k_queue_prepend(queue, node as *mut c_void)
+    } else {
+        C::k_queue_append(queue, node as *mut c_void)
+    };
+    if let Err(e) = ret.neg_err() {
+        drop(Box::from_raw(node));
+        return Err(e);
+    }
+    Ok(())
+}
+
+fn queue_get<C: QueueSyscalls, T>(queue: &KQueue, timeout: k_timeout_t) -> Option<QueueBox<T>> {
+    let node = C::k_queue_get(queue, timeout) as *mut Node<T>;
+    if node.is_null() {
+        None
+    } else {
+        Some(QueueBox(unsafe { Box::from_raw(node) }))
+    }
+}
+
+macro_rules! queue_type {
+    ($(#[$attr:meta])* $name:ident, $insert_doc:literal, $prepend:literal) => {
+        $(#[$attr])*
+        pub struct $name<'q, T> {
+            queue: &'q KQueue,
+            _item: PhantomData<QueueBox<T>>,
+        }
+
+        unsafe impl<'q, T: Send> Send for $name<'q, T> {}
+        unsafe impl<'q, T: Send> Sync for $name<'q, T> {}
+
+        impl<'q, T> $name<'q, T> {
+            /// Unsafe because the caller must ensure the queue is initialized and that every
+            /// item in it was put there by a handle of this type with the same `T`.
+            pub const unsafe fn new(queue: &'q KQueue) -> Self {
+                $name {
+                    queue,
+                    _item: PhantomData,
+                }
+            }
+
+            /// Get the kernel object, e.g. to grant a thread access or to poll on it
+            pub fn kobj(&self) -> &'q KQueue {
+                self.queue
+            }
+
+            #[doc = $insert_doc]
+            ///
+            /// Fails with `NoMemory` if the kernel has to allocate a node and the calling
+            /// thread's resource pool is exhausted or missing. The item is dropped.
+            pub fn put<C: QueueSyscalls>(&self, item: QueueBox<T>) -> Result<()> {
+                unsafe { queue_insert::<C, T>(self.queue, item, $prepend) }
+            }
+
+            /// Get with infinite timeout. Returns None if the wait was cancelled with
+            /// `cancel_wait`.
+            pub fn get<C: QueueSyscalls>(&self) -> Option<QueueBox<T>> {
+                queue_get::<C, T>(self.queue, zephyr_sys::raw::K_FOREVER)
+            }
+
+            /// Get with timeout. Returns None if timeout.
+            pub fn get_timeout<C: QueueSyscalls>(&self, timeout: Timeout) -> Option<QueueBox<T>> {
+                queue_get::<C, T>(self.queue, timeout.0)
+            }
+
+            /// Get with no timeout. Returns None if empty.
+            pub fn try_get<C: QueueSyscalls>(&self) -> Option<QueueBox<T>> {
+                queue_get::<C, T>(self.queue, zephyr_sys::raw::K_NO_WAIT)
+            }
+
+            pub fn is_empty<C: QueueSyscalls>(&self) -> bool {
+                C::k_queue_is_empty(self.queue) != 0
+            }
+
+            /// Wake the first thread waiting in `get`, which then returns None
+            pub fn cancel_wait<C: QueueSyscalls>(&self) {
+                C::k_queue_cancel_wait(self.queue)
+            }
+        }
+
+        impl<'q, T> Clone for $name<'q, T> {
+            fn clone(&self) -> Self {
+                $name {
+                    queue: self.queue,
+                    _item: PhantomData,
+                }
+            }
+        }
+    };
+}
+
+queue_type!(
+    /// First in, first out channel of boxed values over a k_queue, like k_fifo
+    Fifo,
+    "Add an item to the tail of the queue",
+    false
+);
+
+queue_type!(
+    /// Last in, first out channel of boxed values over a k_queue, like k_lifo
+    Lifo,
+    "Add an item to the head of the queue",
+    true
+);
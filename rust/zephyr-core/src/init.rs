@@ -0,0 +1,150 @@
+//! Init functions run by the kernel during boot, like SYS_INIT
+//!
+//! Zephyr 2.5+. Before 2.5 the init table holds a whole `struct device` per entry, which is not
+//! emitted here.
+//!
+//! ```ignore
+//! fn sensor_init() -> zephyr::error::Result<()> {
+//!     Ok(())
+//! }
+//!
+//! zephyr::sys_init!(sensor_init, POST_KERNEL, 50);
+//! ```
+
+use libc::{c_int, c_void};
+
+use crate::error::Result;
+
+/// Function the kernel calls for an init entry. SYS_INIT functions no longer take a device
+/// argument on newer kernels.
+#[cfg(zephyr350)]
+pub type InitFn = extern "C" fn() -> c_int;
+#[cfg(not(zephyr350))]
+pub type InitFn = extern "C" fn(*const c_void) -> c_int;
+
+/// Highest priority the kernel sorts within a level
+#[cfg(zephyr350)]
+pub const MAX_PRIORITY: u32 = 999;
+#[cfg(not(zephyr350))]
+pub const MAX_PRIORITY: u32 = 99;
+
+/// Entry in the kernel's table of init functions, equivalent of `struct init_entry`. Defined with
+/// `sys_init!`.
+#[repr(C)]
+pub struct InitEntry {
+    init: InitFn,
+    dev: *const c_void,
+}
+
+// Only read by the kernel, at boot
+unsafe impl Sync for InitEntry {}
+
+impl InitEntry {
+    pub const fn new(init: InitFn) -> Self {
+        InitEntry {
+            init,
+            dev: core::ptr::null(),
+        }
+    }
+}
+
+/// The init return code for `result`: 0 or a negative errno
+pub fn return_code(result: Result<()>) -> c_int {
+    match result {
+        Ok(()) => 0,
+        Err(e) => -(e.errno() as c_int),
+    }
+}
+
+/// `sys_init!(init_fn, LEVEL, priority)` registers `fn init_fn() -> zephyr::error::Result<()>` to
+/// be run by the kernel during boot, like SYS_INIT. `LEVEL` is one of `PRE_KERNEL_1`,
+/// `PRE_KERNEL_2`, `POST_KERNEL` or `APPLICATION`, and `priority` is an integer literal ordering
+/// functions within the level, lowest first. An error is returned to the kernel as a negative
+/// errno.
+///
+/// PRE_KERNEL functions run before the scheduler and must not block or allocate from the heap.
+#[macro_export]
+macro_rules! sys_init {
+    ($init:path, $level:ident, $prio:literal) => {
+        $crate::init_level!($level);
+
+        const _: () = {
+            assert!(
+                $prio <= $crate::init::MAX_PRIORITY,
+                "init priority is out of range"
+            );
+
+            $crate::init_fn!(
+                fn init() -> core::ffi::c_int {
+                    $crate::init::return_code($init())
+                }
+            );
+
+            #[used]
+            #[link_section = $crate::init_section!($level, $prio)]
+            static ENTRY: $crate::init::InitEntry = $crate::init::InitEntry::new(init);
+        };
+    };
+}
+
+/// Used by `sys_init!` to reject unknown init levels
+#[doc(hidden)]
+#[macro_export]
+macro_rules! init_level {
+    (PRE_KERNEL_1) => {};
+    (PRE_KERNEL_2) => {};
+    (POST_KERNEL) => {};
+    (APPLICATION) => {};
+    ($level:ident) => {
+        compile_error!(concat!(
+            "unknown init level ",
+            stringify!($level),
+            ", expected PRE_KERNEL_1, PRE_KERNEL_2, POST_KERNEL or APPLICATION"
+        ));
+    };
+}
+
+/// Used by `sys_init!`. Defines the function the kernel calls, matching `InitFn`.
+#[cfg(zephyr350)]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! init_fn {
+    (fn $name:ident() -> $ret:ty $body:block) => {
+        extern "C" fn $name() -> $ret $body
+    };
+}
+/// See above. Ignores the device argument.
+#[cfg(not(zephyr350))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! init_fn {
+    (fn $name:ident() -> $ret:ty $body:block) => {
+        extern "C" fn $name(_: *const core::ffi::c_void) -> $ret $body
+    };
+}
+
+/// Used by `sys_init!`. Section of the init table for the level, sorted by priority. Equivalent of
+/// Z_INIT_ENTRY_SECTION.
+#[cfg(zephyr350)]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! init_section {
+    ($level:ident, $prio:literal) => {
+        concat!(
+            ".z_init_",
+            stringify!($level),
+            "_P_",
+            stringify!($prio),
+            "_SUB_0_"
+        )
+    };
+}
+/// See above. Only priorities up to 99 are sorted.
+#[cfg(not(zephyr350))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! init_section {
+    ($level:ident, $prio:literal) => {
+        concat!(".z_init_", stringify!($level), stringify!($prio), "_")
+    };
+}
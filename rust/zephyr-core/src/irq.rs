@@ -0,0 +1,47 @@
+//! Interrupt locking for data shared with ISRs
+//!
+//! irq_lock is an inline function in Zephyr, so it is called through a C wrapper. Locking
+//! interrupts is a privileged operation, so this is kernel-only.
+
+use core::marker::PhantomData;
+
+use libc::c_uint;
+
+/// Guard that keeps interrupts locked on the current CPU until dropped, like irq_lock and
+/// irq_unlock
+///
+/// Locks nest. Interrupts are only unlocked when the outermost guard is dropped. The thread may
+/// still be switched out if it blocks while holding the lock, in which case interrupts are
+/// locked again when it resumes. On SMP this is a global lock shared by all CPUs, so prefer
+/// `SpinMutex` for new code.
+pub struct IrqLock {
+    key: c_uint,
+    // Must be unlocked on the thread that locked it
+    _not_send: PhantomData<*const ()>,
+}
+
+impl IrqLock {
+    pub fn new<C: IrqSyscalls>() -> Self {
+        IrqLock {
+            key: C::irq_lock(),
+            _not_send: PhantomData,
+        }
+    }
+}
+
+impl Drop for IrqLock {
+    fn drop(&mut self) {
+        unsafe { zephyr_sys::raw::rust_irq_unlock(self.key) }
+    }
+}
+
+/// Raw API. Only implemented for the kernel context.
+pub trait IrqSyscalls {
+    fn irq_lock() -> c_uint;
+}
+
+impl IrqSyscalls for crate::context::Kernel {
+    fn irq_lock() -> c_uint {
+        unsafe { zephyr_sys::raw::rust_irq_lock() }
+    }
+}
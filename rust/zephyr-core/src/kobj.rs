@@ -22,6 +22,7 @@ unsafe impl KObj for zephyr_sys::raw::device {
     const OTYPE: k_objects = zephyr_sys::raw::k_objects_K_OBJ_ANY;
 }
 
+#[repr(transparent)]
 pub struct StaticKObj<T>(UnsafeCell<MaybeUninit<T>>);
 
 unsafe impl<T: KObj> Send for StaticKObj<T> {}
@@ -33,6 +34,17 @@ impl<T> StaticKObj<T> {
     pub const unsafe fn uninit() -> Self {
         StaticKObj(UnsafeCell::new(MaybeUninit::uninit()))
     }
+
+    /// Already initialized, e.g. at compile time with the object's `initializer`
+    pub const fn new(obj: T) -> Self {
+        StaticKObj(UnsafeCell::new(MaybeUninit::new(obj)))
+    }
+
+    /// Same as deref, but usable in const context so a static can hold a reference to the
+    /// kernel object. The object is still uninitialized until its constructor runs.
+    pub const fn kobj(&self) -> &T {
+        unsafe { &*(self.0.get() as *const T) }
+    }
 }
 
 impl<T: KObj> StaticKObj<T> {
@@ -69,6 +81,7 @@ macro_rules! make_static_wrapper {
             use core::ops::Deref;
 
             #[allow(non_camel_case_types)]
+            #[repr(transparent)]
             pub struct $k_type(StaticKObj<$k_path>);
 
             unsafe impl KObj for $k_type {
@@ -80,9 +93,13 @@ macro_rules! make_static_wrapper {
                     $k_type(StaticKObj::uninit())
                 }
 
+                pub const fn new(obj: $k_path) -> Self {
+                    $k_type(StaticKObj::new(obj))
+                }
+
                 /// Get the real k_obj type. Same as deref twice.
-                pub fn kobj(&self) -> &$k_path {
-                    self.deref().deref()
+                pub const fn kobj(&self) -> &$k_path {
+                    self.0.kobj()
                 }
             }
 
@@ -96,3 +113,82 @@ macro_rules! make_static_wrapper {
         }
     };
 }
+
+/// Equivalent of Z_WAIT_Q_INIT for the wait queue at `wait_q`. An empty queue is a list pointing to
+/// itself. The scalable wait queue is not supported, so RUST_STATIC_KOBJ_INIT depends on
+/// !WAITQ_SCALABLE.
+#[cfg(static_kobj_init)]
+pub(crate) const fn wait_q_init(
+    wait_q: *const zephyr_sys::raw::_wait_q_t,
+) -> zephyr_sys::raw::_wait_q_t {
+    let wait_q = wait_q as *mut libc::c_void;
+    unsafe { core::mem::transmute([wait_q, wait_q]) }
+}
+
+/// Equivalent of SYS_DLIST_STATIC_INIT for the list at `list`
+#[cfg(static_kobj_init)]
+pub(crate) const fn dlist_init(
+    list: *const zephyr_sys::raw::sys_dlist_t,
+) -> zephyr_sys::raw::sys_dlist_t {
+    let list = list as *mut libc::c_void;
+    unsafe { core::mem::transmute([list, list]) }
+}
+
+/// Used by the `k_*_define!` macros for objects that can be initialized at compile time. With
+/// RUST_STATIC_KOBJ_INIT, the static is set to `$initializer`, so it is valid before any
+/// constructor runs. Otherwise `$init` initializes it from a constructor.
+#[cfg(static_kobj_init)]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! kobj_static {
+    ($section:literal, $ident:ident: $type:path = $initializer:expr,
+     $ctor_ptr:ident = $ctor:ident $init:block) => {
+        #[link_section = $section]
+        #[allow(non_upper_case_globals)]
+        static $ident: $type = unsafe { $initializer };
+    };
+}
+/// See above. Initializes the static from a constructor.
+#[cfg(not(static_kobj_init))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! kobj_static {
+    ($section:literal, $ident:ident: $type:path = $initializer:expr,
+     $ctor_ptr:ident = $ctor:ident $init:block) => {
+        #[link_section = $section]
+        #[allow(non_upper_case_globals)]
+        static $ident: $type = unsafe { <$type>::uninit() };
+
+        // A constructor function that calls its init
+        #[allow(non_snake_case)]
+        extern "C" fn $ctor() $init
+
+        $crate::kobj_ctor!($ctor_ptr = $ctor);
+    };
+}
+
+/// Used by the `k_*_define!` macros to add a pointer to a constructor to the .ctors table
+#[cfg(ctors)]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! kobj_ctor {
+    ($ctor_ptr:ident = $ctor:ident) => {
+        #[used]
+        #[link_section = ".ctors"]
+        #[allow(non_upper_case_globals)]
+        static $ctor_ptr: extern "C" fn() = $ctor;
+    };
+}
+/// Used by the `k_*_define!` macros. Nothing would run the constructor.
+#[cfg(not(ctors))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! kobj_ctor {
+    ($ctor_ptr:ident = $ctor:ident) => {
+        compile_error!(concat!(
+            "this kernel object is initialized by a constructor, which needs ",
+            "CONFIG_STATIC_INIT_GNU (Zephyr 3.7+), CONFIG_CPP (3.3+) ",
+            "or CONFIG_CPLUSPLUS (before 3.3)"
+        ));
+    };
+}
@@ -0,0 +1,153 @@
+//! Kernel objects allocated at runtime with k_object_alloc
+//!
+//! Unlike a boxed object, a dynamically allocated kernel object is tracked by the kernel, so user
+//! mode threads can create and use them. Requires CONFIG_DYNAMIC_OBJECTS.
+//!
+//! The kernel frees an object once no thread has permission on it. The allocating thread is
+//! granted permission by k_object_alloc and gives it up when the `DynKObj` is dropped. Other
+//! threads granted access keep the object alive until they exit or release it themselves.
+
+use core::mem;
+use core::ops::Deref;
+use core::ptr::NonNull;
+
+use libc::c_void;
+use zephyr_sys::raw::k_objects;
+
+use super::NegErr;
+use crate::error::{Error, Result};
+use crate::kobj::KObj;
+use crate::msgq::{KMsgq, MsgqSyscalls};
+use crate::pipe::{KPipe, PipeSyscalls};
+use crate::poll::{KPollSignal, KPollSignalSyscalls};
+use crate::semaphore::{KSem, SemaphoreSyscalls};
+
+/// Raw syscall API
+pub trait KObjAllocSyscalls {
+    fn k_object_alloc(otype: k_objects) -> *mut c_void;
+    unsafe fn k_object_release(object: *const c_void);
+}
+
+macro_rules! trait_impl {
+    ($context:ident, $context_struct:path) => {
+        impl KObjAllocSyscalls for $context_struct {
+            fn k_object_alloc(otype: k_objects) -> *mut c_void {
+                unsafe { zephyr_sys::syscalls::$context::k_object_alloc(otype) }
+            }
+
+            unsafe fn k_object_release(object: *const c_void) {
+                zephyr_sys::syscalls::$context::k_object_release(object)
+            }
+        }
+    };
+}
+
+trait_impl!(kernel, crate::context::Kernel);
+trait_impl!(user, crate::context::User);
+trait_impl!(any, crate::context::Any);
+
+/// Owned, dynamically allocated kernel object
+///
+/// Construct with `new` for the supported object types, which allocates and initializes the
+/// object. Fails with `NoMemory` if out of memory. Dropping releases the current thread's
+/// permission, using the runtime-detect context since the dropping context is not known.
+pub struct DynKObj<T: KObj>(NonNull<T>);
+
+unsafe impl<T: KObj> Send for DynKObj<T> {}
+unsafe impl<T: KObj> Sync for DynKObj<T> {}
+
+impl<T: KObj> DynKObj<T> {
+    /// Allocate an object of type `T`. Unsafe because it is uninitialized and must be
+    /// initialized by its init syscall before any other use.
+    pub unsafe fn alloc<C: KObjAllocSyscalls>() -> Result<Self> {
+        NonNull::new(C::k_object_alloc(T::OTYPE) as *mut T)
+            .map(DynKObj)
+            .ok_or(Error::NoMemory)
+    }
+
+    /// Initialize a newly allocated object, releasing it if `init` fails
+    unsafe fn alloc_init<C, F>(init: F) -> Result<Self>
+    where
+        C: KObjAllocSyscalls,
+        F: FnOnce(&T) -> Result<u32>,
+    {
+        let obj = Self::alloc::<C>()?;
+        init(&obj)?;
+        Ok(obj)
+    }
+
+    pub fn into_raw(self) -> *mut T {
+        let ptr = self.0.as_ptr();
+        mem::forget(self);
+        ptr
+    }
+
+    /// Unsafe because `obj` must be from `into_raw` and the current thread must still hold
+    /// permission on it.
+    pub unsafe fn from_raw(obj: *mut T) -> Self {
+        DynKObj(NonNull::new_unchecked(obj))
+    }
+}
+
+impl<T: KObj> Deref for DynKObj<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { self.0.as_ref() }
+    }
+}
+
+impl<T: KObj> Drop for DynKObj<T> {
+    fn drop(&mut self) {
+        unsafe { crate::context::Any::k_object_release(self.0.as_ptr() as *const c_void) }
+    }
+}
+
+impl DynKObj<KSem> {
+    pub fn new<C: KObjAllocSyscalls + SemaphoreSyscalls>(
+        initial_count: u32,
+        limit: u32,
+    ) -> Result<Self> {
+        unsafe {
+            Self::alloc_init::<C, _>(|sem| C::k_sem_init(sem, initial_count, limit).neg_err())
+        }
+    }
+}
+
+impl DynKObj<KPollSignal> {
+    pub fn new<C: KObjAllocSyscalls + KPollSignalSyscalls>() -> Result<Self> {
+        unsafe {
+            Self::alloc_init::<C, _>(|signal| {
+                C::k_poll_signal_init(signal);
+                Ok(0)
+            })
+        }
+    }
+}
+
+impl DynKObj<KMsgq> {
+    /// Message queue holding up to `max_msgs` messages of type `T`. The buffer is allocated from
+    /// the calling thread's resource pool and freed with the queue. Use `MsgQueue::<T>::new` for
+    /// typed access.
+    pub fn new<C: KObjAllocSyscalls + MsgqSyscalls, T: Copy>(max_msgs: u32) -> Result<Self> {
+        assert!(mem::size_of::<T>() != 0, "message type must not be zero sized");
+        // The resource pool only guarantees pointer alignment
+        assert!(
+            mem::align_of::<T>() <= mem::size_of::<usize>(),
+            "message type alignment is larger than the resource pool provides"
+        );
+        unsafe {
+            Self::alloc_init::<C, _>(|msgq| {
+                C::k_msgq_alloc_init(msgq, mem::size_of::<T>(), max_msgs).neg_err()
+            })
+        }
+    }
+}
+
+impl DynKObj<KPipe> {
+    /// Pipe with a `size` byte ring buffer. The buffer is allocated from the calling thread's
+    /// resource pool and freed with the pipe.
+    pub fn new<C: KObjAllocSyscalls + PipeSyscalls>(size: usize) -> Result<Self> {
+        unsafe { Self::alloc_init::<C, _>(|pipe| C::k_pipe_alloc_init(pipe, size).neg_err()) }
+    }
+}
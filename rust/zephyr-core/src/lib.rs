@@ -1,20 +1,44 @@
 #![cfg_attr(not(feature = "have_std"), no_std)]
 #![feature(never_type)]
+#![feature(panic_info_message)]
 
 #[macro_use]
 extern crate derive_more;
 
+#[cfg(zephyr250)]
+pub mod condvar;
+pub mod error;
+#[cfg(events)]
+pub mod event;
+pub mod fifo;
+#[cfg(zephyr250)]
+pub mod init;
+pub mod irq;
 pub mod kobj;
+#[cfg(dynamic_objects)]
+pub mod kobj_alloc;
 pub mod memdomain;
+pub mod memslab;
 pub mod mempool;
+pub mod msgq;
 pub mod mutex;
 pub mod mutex_alloc;
+pub mod panic;
+pub mod pipe;
 pub mod poll;
 mod poll_signal;
+pub mod rwlock;
 pub mod semaphore;
+#[cfg(shell)]
+pub mod shell;
+pub mod spinlock;
 pub mod thread;
+pub mod timer;
+#[cfg(zephyr260)]
+pub mod work;
 mod time;
 
+pub use error::Error;
 pub use time::*;
 use zephyr_sys::raw::KERNEL_VERSION_NUMBER;
 use core::fmt;
@@ -43,15 +67,15 @@ crate::global_sys_mem_pool!(rust_std_mem_pool);
 
 /// Convert a negative error code to a Result
 pub trait NegErr {
-    fn neg_err(&self) -> Result<u32, u32>;
+    fn neg_err(&self) -> Result<u32, Error>;
 }
 
 impl NegErr for i32 {
-    fn neg_err(&self) -> Result<u32, u32> {
+    fn neg_err(&self) -> Result<u32, Error> {
         if *self >= 0 {
             Ok(*self as u32)
         } else {
-            Err((-*self) as u32)
+            Err(Error::from_errno((-*self) as u32))
         }
     }
 }
@@ -142,9 +166,8 @@ macro_rules! zephyr_bindings {
                 zephyr_sys::syscalls::$context::k_mutex_lock(mutex, timeout)
             }
 
-            unsafe fn k_mutex_unlock(mutex: *mut zephyr_sys::raw::k_mutex) {
-                // TODO: return the error from here. Ignoring now for Zephyr 2.1 compat
-                zephyr_sys::syscalls::$context::k_mutex_unlock(mutex);
+            unsafe fn k_mutex_unlock(mutex: *mut zephyr_sys::raw::k_mutex) -> libc::c_int {
+                zephyr_sys::syscalls::$context::k_mutex_unlock(mutex)
             }
         }
     };
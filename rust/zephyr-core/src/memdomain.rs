@@ -1,8 +1,25 @@
+//! Memory domains and partitions, which control what memory user mode threads can access
+//!
+//! Every user thread belongs to exactly one memory domain, which is a set of partitions. Domains
+//! can be defined from Rust with `k_mem_domain_define!` to isolate user mode components from each
+//! other, instead of putting every Rust thread in `rust_std_domain`.
+//!
+//! Domain and partition management is kernel-only. Without CONFIG_USERSPACE, everything here is a
+//! no-op so the same code builds either way.
+
+use core::cell::UnsafeCell;
 #[cfg(not(usermode))]
 use core::marker::PhantomData;
+#[cfg(usermode)]
+use core::mem::MaybeUninit;
 
 pub use zephyr_sys::raw::k_mem_domain;
+#[cfg(usermode)]
+pub use zephyr_sys::raw::k_mem_partition;
 
+#[cfg(all(usermode, zephyr260))]
+use super::NegErr;
+use crate::error::Result;
 use crate::thread::ThreadId;
 
 #[cfg(usermode)]
@@ -10,32 +27,253 @@ pub struct MemDomain<'a>(&'a k_mem_domain);
 #[cfg(not(usermode))]
 pub struct MemDomain<'a>(PhantomData<&'a ()>);
 
+unsafe impl<'a> Send for MemDomain<'a> {}
+unsafe impl<'a> Sync for MemDomain<'a> {}
+
 impl<'a> MemDomain<'a> {
     #[cfg(usermode)]
-    pub unsafe fn new(domain: &'a k_mem_domain) -> Self {
+    pub const unsafe fn new(domain: &'a k_mem_domain) -> Self {
         MemDomain(domain)
     }
     #[cfg(not(usermode))]
-    pub fn new() -> Self {
+    pub const fn new() -> Self {
         MemDomain(PhantomData)
     }
 
+    /// Move a thread into this domain. A thread is in exactly one domain, so this removes it from
+    /// its previous one.
     pub fn add_thread<C: MemDomainAPI>(&self, _thread: ThreadId) {
         #[cfg(usermode)]
         C::k_mem_domain_add_thread(self.0, _thread)
     }
+
+    /// Take a thread out of whatever domain it is in. Since Zephyr 2.5 this moves it to the
+    /// default domain.
+    pub fn remove_thread<C: MemDomainAPI>(_thread: ThreadId) {
+        #[cfg(usermode)]
+        C::k_mem_domain_remove_thread(_thread)
+    }
+
+    /// Add a partition. Fails with `NoSpace` if the domain is full, or `InvalidArgument` if the
+    /// partition overlaps one already in it. Zephyr before 2.6 asserts instead.
+    pub fn add_partition<C: MemDomainAPI>(&self, _partition: &'a MemPartition) -> Result<()> {
+        #[cfg(usermode)]
+        return C::k_mem_domain_add_partition(self.0, _partition.as_ptr());
+        #[cfg(not(usermode))]
+        Ok(())
+    }
+
+    /// Remove a partition. Fails with `NoEntry` if it is not in the domain. Zephyr before 2.6
+    /// asserts instead.
+    pub fn remove_partition<C: MemDomainAPI>(&self, _partition: &MemPartition) -> Result<()> {
+        #[cfg(usermode)]
+        return C::k_mem_domain_remove_partition(self.0, _partition.as_ptr());
+        #[cfg(not(usermode))]
+        Ok(())
+    }
 }
 
 pub trait MemDomainAPI {
+    #[cfg(usermode)]
+    unsafe fn k_mem_domain_init(domain: &k_mem_domain) -> Result<()>;
     fn k_mem_domain_add_thread(domain: &k_mem_domain, thread: ThreadId);
+    #[cfg(usermode)]
+    fn k_mem_domain_remove_thread(thread: ThreadId);
+    #[cfg(usermode)]
+    fn k_mem_domain_add_partition(
+        domain: &k_mem_domain,
+        partition: *mut k_mem_partition,
+    ) -> Result<()>;
+    #[cfg(usermode)]
+    fn k_mem_domain_remove_partition(
+        domain: &k_mem_domain,
+        partition: *mut k_mem_partition,
+    ) -> Result<()>;
 }
 
 impl MemDomainAPI for crate::context::Kernel {
+    #[cfg(usermode)]
+    unsafe fn k_mem_domain_init(domain: &k_mem_domain) -> Result<()> {
+        let ret = zephyr_sys::raw::k_mem_domain_init(
+            domain as *const _ as *mut _,
+            0,
+            core::ptr::null_mut(),
+        );
+        // Errors are returned since Zephyr 2.6
+        #[cfg(zephyr260)]
+        return ret.neg_err().map(|_| ());
+        #[cfg(not(zephyr260))]
+        {
+            let _ = ret;
+            Ok(())
+        }
+    }
+
     fn k_mem_domain_add_thread(domain: &k_mem_domain, thread: ThreadId) {
         unsafe {
             zephyr_sys::raw::k_mem_domain_add_thread(domain as *const _ as *mut _, thread.tid());
         }
     }
+
+    #[cfg(usermode)]
+    fn k_mem_domain_remove_thread(thread: ThreadId) {
+        unsafe {
+            #[cfg(zephyr250)]
+            zephyr_sys::raw::k_mem_domain_add_thread(
+                core::ptr::addr_of_mut!(zephyr_sys::raw::k_mem_domain_default),
+                thread.tid(),
+            );
+            #[cfg(not(zephyr250))]
+            zephyr_sys::raw::k_mem_domain_remove_thread(thread.tid());
+        }
+    }
+
+    #[cfg(usermode)]
+    fn k_mem_domain_add_partition(
+        domain: &k_mem_domain,
+        partition: *mut k_mem_partition,
+    ) -> Result<()> {
+        let ret = unsafe {
+            zephyr_sys::raw::k_mem_domain_add_partition(domain as *const _ as *mut _, partition)
+        };
+        #[cfg(zephyr260)]
+        return ret.neg_err().map(|_| ());
+        #[cfg(not(zephyr260))]
+        {
+            let _ = ret;
+            Ok(())
+        }
+    }
+
+    #[cfg(usermode)]
+    fn k_mem_domain_remove_partition(
+        domain: &k_mem_domain,
+        partition: *mut k_mem_partition,
+    ) -> Result<()> {
+        let ret = unsafe {
+            zephyr_sys::raw::k_mem_domain_remove_partition(domain as *const _ as *mut _, partition)
+        };
+        #[cfg(zephyr260)]
+        return ret.neg_err().map(|_| ());
+        #[cfg(not(zephyr260))]
+        {
+            let _ = ret;
+            Ok(())
+        }
+    }
+}
+
+/// Storage for a memory domain defined in Rust. Define with `k_mem_domain_define!`, which keeps
+/// it out of memory user threads can write.
+pub struct StaticMemDomain {
+    #[cfg(usermode)]
+    domain: UnsafeCell<MaybeUninit<k_mem_domain>>,
+}
+
+unsafe impl Sync for StaticMemDomain {}
+
+impl StaticMemDomain {
+    /// Unsafe because it must be initialized with `init` before use
+    pub const unsafe fn uninit() -> Self {
+        StaticMemDomain {
+            #[cfg(usermode)]
+            domain: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+
+    /// Initialize as an empty domain
+    pub unsafe fn init<C: MemDomainAPI>(&self) -> Result<()> {
+        #[cfg(usermode)]
+        return C::k_mem_domain_init(self.domain());
+        #[cfg(not(usermode))]
+        Ok(())
+    }
+
+    #[cfg(usermode)]
+    const fn domain(&self) -> &k_mem_domain {
+        unsafe { &*(self.domain.get() as *const k_mem_domain) }
+    }
+
+    pub const fn mem_domain(&self) -> MemDomain {
+        #[cfg(usermode)]
+        unsafe {
+            MemDomain::new(self.domain())
+        }
+        #[cfg(not(usermode))]
+        MemDomain::new()
+    }
+}
+
+/// What user threads in a domain may do with a partition's memory. Supervisor mode always has
+/// read/write access.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UserAccess {
+    ReadWrite = 0,
+    ReadOnly = 1,
+    None = 2,
+}
+
+/// A memory partition, which is a region of memory with access permissions
+///
+/// Either a partition defined in C and referenced with `static_mem_partition!`, e.g. an app
+/// shared memory partition from K_APPMEM_PARTITION_DEFINE, or one with a buffer defined in Rust
+/// with `k_mem_partition_define!`. Without CONFIG_USERSPACE only the region is kept.
+#[repr(transparent)]
+pub struct MemPartition(
+    #[cfg(usermode)] UnsafeCell<MaybeUninit<k_mem_partition>>,
+    #[cfg(not(usermode))] UnsafeCell<(usize, usize)>,
+);
+
+unsafe impl Sync for MemPartition {}
+
+impl MemPartition {
+    /// Unsafe because it must be initialized with `init` before use
+    pub const unsafe fn uninit() -> Self {
+        #[cfg(usermode)]
+        return MemPartition(UnsafeCell::new(MaybeUninit::uninit()));
+        #[cfg(not(usermode))]
+        return MemPartition(UnsafeCell::new((0, 0)));
+    }
+
+    /// Set the region and its permissions
+    ///
+    /// Unsafe because the partition must not be in any domain yet, and the region must meet the
+    /// architecture's size and alignment rules, e.g. a power of two size aligned to its size on
+    /// ARMv7-M, or whole pages on x86.
+    pub unsafe fn init(&self, start: *mut u8, size: usize, _access: UserAccess) {
+        #[cfg(usermode)]
+        zephyr_sys::raw::rust_k_mem_partition_init(
+            self.0.get() as *mut k_mem_partition,
+            start as usize,
+            size,
+            _access as libc::c_int,
+        );
+        #[cfg(not(usermode))]
+        {
+            *self.0.get() = (start as usize, size);
+        }
+    }
+
+    #[cfg(usermode)]
+    fn as_ptr(&self) -> *mut k_mem_partition {
+        self.0.get() as *mut k_mem_partition
+    }
+
+    /// Start of the region
+    pub fn start(&self) -> *mut u8 {
+        #[cfg(usermode)]
+        return unsafe { (*self.as_ptr()).start as *mut u8 };
+        #[cfg(not(usermode))]
+        return unsafe { (*self.0.get()).0 as *mut u8 };
+    }
+
+    /// Size of the region in bytes
+    pub fn size(&self) -> usize {
+        #[cfg(usermode)]
+        return unsafe { (*self.as_ptr()).size as usize };
+        #[cfg(not(usermode))]
+        return unsafe { (*self.0.get()).1 };
+    }
 }
 
 /// Get a static reference to an external mem domain
@@ -59,3 +297,50 @@ macro_rules! static_mem_domain {
         $crate::memdomain::MemDomain::new()
     }};
 }
+
+/// Get a static reference to an external mem partition, e.g. one defined with
+/// K_APPMEM_PARTITION_DEFINE, which Rust statics can be put in with `#[app_dmem]`
+#[cfg(usermode)]
+#[macro_export]
+macro_rules! static_mem_partition {
+    ($partition:ident) => {{
+        extern "C" {
+            #[no_mangle]
+            static $partition: $crate::memdomain::MemPartition;
+        }
+
+        unsafe { &$partition }
+    }};
+}
+/// Get a static reference to an external mem partition, e.g. one defined with
+/// K_APPMEM_PARTITION_DEFINE. Without CONFIG_USERSPACE the partition is empty.
+#[cfg(not(usermode))]
+#[macro_export]
+macro_rules! static_mem_partition {
+    ($partition:ident) => {{
+        static PARTITION: $crate::memdomain::MemPartition =
+            unsafe { $crate::memdomain::MemPartition::uninit() };
+        &PARTITION
+    }};
+}
+
+/// Used by `#[app_dmem]` and `#[app_bmem]` to put a static in a partition's section
+#[cfg(usermode)]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! app_mem_static {
+    ($section:literal, $item:item) => {
+        #[link_section = $section]
+        $item
+    };
+}
+/// Used by `#[app_dmem]` and `#[app_bmem]`. App memory sections only exist with
+/// CONFIG_USERSPACE.
+#[cfg(not(usermode))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! app_mem_static {
+    ($section:literal, $item:item) => {
+        $item
+    };
+}
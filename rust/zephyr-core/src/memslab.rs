@@ -0,0 +1,177 @@
+//! Fixed-size block allocation from a k_mem_slab
+//!
+//! A slab hands out blocks of one size from a static buffer in constant time, so unlike the heap
+//! it can be used from ISRs (with `K_NO_WAIT`) and never fragments. Each `MemSlab<T>` holds
+//! blocks sized for one `T`. The k_mem_slab API is not available to user mode, so this is
+//! kernel-only.
+
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem::{ManuallyDrop, MaybeUninit};
+use core::ops::{Deref, DerefMut};
+use core::ptr::{self, NonNull};
+
+use libc::c_void;
+use zephyr_sys::raw::k_mem_slab;
+
+use super::NegErr;
+use crate::error::Error;
+use crate::time::Timeout;
+
+pub use zephyr_sys::raw::k_mem_slab as KMemSlab;
+
+/// One block. The kernel keeps its free list in the first word of free blocks, so a block is
+/// at least a pointer in size and alignment.
+#[repr(C)]
+union Block<T> {
+    _item: ManuallyDrop<T>,
+    _link: *mut c_void,
+}
+
+/// Backing storage for `N` blocks of `T`
+///
+/// Define with `k_mem_slab_define!` so it is not placed in the Rust user partition.
+#[repr(C)]
+pub struct SlabBuffer<T, const N: usize>(UnsafeCell<MaybeUninit<[Block<T>; N]>>);
+
+unsafe impl<T, const N: usize> Sync for SlabBuffer<T, N> {}
+
+impl<T, const N: usize> SlabBuffer<T, N> {
+    pub const fn new() -> Self {
+        SlabBuffer(UnsafeCell::new(MaybeUninit::uninit()))
+    }
+}
+
+/// A slab of blocks for values of type `T`
+///
+/// Has the layout of `k_mem_slab`, so a static can be placed with the kernel's statically defined
+/// slabs.
+#[repr(transparent)]
+pub struct MemSlab<T> {
+    slab: UnsafeCell<MaybeUninit<k_mem_slab>>,
+    _item: PhantomData<T>,
+}
+
+unsafe impl<T: Send> Send for MemSlab<T> {}
+unsafe impl<T: Send> Sync for MemSlab<T> {}
+
+impl<T> MemSlab<T> {
+    /// Unsafe because it must be initialized with `init` before use
+    pub const unsafe fn uninit() -> Self {
+        MemSlab {
+            slab: UnsafeCell::new(MaybeUninit::uninit()),
+            _item: PhantomData,
+        }
+    }
+
+    /// Initialize with the buffer to allocate from. Fails with `InvalidArgument` if the kernel
+    /// rejects the block size or buffer alignment.
+    ///
+    /// Unsafe because the slab must not be in use, and the buffer must not be used by any other
+    /// slab.
+    pub unsafe fn init<const N: usize>(
+        &self,
+        buffer: &'static SlabBuffer<T, N>,
+    ) -> crate::error::Result<()> {
+        zephyr_sys::raw::k_mem_slab_init(
+            self.as_ptr(),
+            buffer.0.get() as *mut c_void,
+            core::mem::size_of::<Block<T>>(),
+            N as u32,
+        )
+        .neg_err()
+        .map(|_| ())
+    }
+
+    fn as_ptr(&self) -> *mut k_mem_slab {
+        self.slab.get() as *mut k_mem_slab
+    }
+
+    /// Move `value` into a block, waiting up to `timeout` for one to be free. On failure the
+    /// value is given back with the error, which is `Again` on timeout, or `NoMemory` if none
+    /// was free and the timeout is `K_NO_WAIT`.
+    pub fn alloc(&self, value: T, timeout: Timeout) -> Result<SlabBox<T>, (Error, T)> {
+        let mut block: *mut c_void = ptr::null_mut();
+        if let Err(e) =
+            unsafe { zephyr_sys::raw::k_mem_slab_alloc(self.as_ptr(), &mut block, timeout.0) }
+                .neg_err()
+        {
+            return Err((e, value));
+        }
+        let ptr = block as *mut T;
+        unsafe { ptr.write(value) };
+        Ok(SlabBox {
+            ptr: unsafe { NonNull::new_unchecked(ptr) },
+            slab: self,
+        })
+    }
+
+    /// Move `value` into a block if one is free. Gives the value back if not.
+    pub fn try_alloc(&self, value: T) -> Result<SlabBox<T>, (Error, T)> {
+        self.alloc(value, crate::K_NO_WAIT)
+    }
+
+    /// Number of blocks allocated
+    pub fn num_used(&self) -> u32 {
+        unsafe { zephyr_sys::raw::rust_k_mem_slab_num_used_get(self.as_ptr()) }
+    }
+
+    /// Number of blocks available
+    pub fn num_free(&self) -> u32 {
+        unsafe { zephyr_sys::raw::rust_k_mem_slab_num_free_get(self.as_ptr()) }
+    }
+}
+
+/// A value owned in a slab block, freed back to the slab on drop
+pub struct SlabBox<'a, T> {
+    ptr: NonNull<T>,
+    slab: &'a MemSlab<T>,
+}
+
+unsafe impl<'a, T: Send> Send for SlabBox<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for SlabBox<'a, T> {}
+
+impl<'a, T> SlabBox<'a, T> {
+    /// Move the value out, freeing the block
+    pub fn into_inner(b: Self) -> T {
+        let b = ManuallyDrop::new(b);
+        unsafe {
+            let value = b.ptr.as_ptr().read();
+            zephyr_sys::raw::rust_k_mem_slab_free(b.slab.as_ptr(), b.ptr.as_ptr() as *mut c_void);
+            value
+        }
+    }
+}
+
+impl<'a, T> Deref for SlabBox<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<'a, T> DerefMut for SlabBox<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { self.ptr.as_mut() }
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for SlabBox<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<'a, T> Drop for SlabBox<'a, T> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::drop_in_place(self.ptr.as_ptr());
+            zephyr_sys::raw::rust_k_mem_slab_free(
+                self.slab.as_ptr(),
+                self.ptr.as_ptr() as *mut c_void,
+            );
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use core::cell::UnsafeCell;
+use core::marker::PhantomData;
+use core::mem::{self, MaybeUninit};
+
+use libc::{c_int, c_void};
+use zephyr_sys::raw::{k_msgq, k_objects, k_timeout_t};
+
+use super::NegErr;
+use crate::error::Result;
+use crate::kobj::*;
+use crate::time::Timeout;
+
+// Declare the Zephyr struct to be a kernel object
+unsafe impl KObj for k_msgq {
+    const OTYPE: k_objects = zephyr_sys::raw::k_objects_K_OBJ_MSGQ;
+}
+
+pub use zephyr_sys::raw::k_msgq as KMsgq;
+
+crate::make_static_wrapper!(k_msgq, zephyr_sys::raw::k_msgq);
+
+/// Raw syscall API
+pub trait MsgqSyscalls {
+    /// Allocates the buffer from the calling thread's resource pool
+    unsafe fn k_msgq_alloc_init(msgq: &k_msgq, msg_size: usize, max_msgs: u32) -> c_int;
+    unsafe fn k_msgq_put(msgq: &k_msgq, data: *const c_void, timeout: k_timeout_t) -> c_int;
+    unsafe fn k_msgq_get(msgq: &k_msgq, data: *mut c_void, timeout: k_timeout_t) -> c_int;
+    unsafe fn k_msgq_peek(msgq: &k_msgq, data: *mut c_void) -> c_int;
+    fn k_msgq_purge(msgq: &k_msgq);
+    fn k_msgq_num_free_get(msgq: &k_msgq) -> u32;
+    fn k_msgq_num_used_get(msgq: &k_msgq) -> u32;
+}
+
+macro_rules! trait_impl {
+    ($context:ident, $context_struct:path) => {
+        impl MsgqSyscalls for $context_struct {
+            unsafe fn k_msgq_alloc_init(msgq: &k_msgq, msg_size: usize, max_msgs: u32) -> c_int {
+                zephyr_sys::syscalls::$context::k_msgq_alloc_init(
+                    msgq as *const _ as *mut _,
+                    msg_size,
+                    max_msgs,
+                )
+            }
+
+            unsafe fn k_msgq_put(
+                msgq: &k_msgq,
+                data: *const c_void,
+                timeout: k_timeout_t,
+            ) -> c_int {
+                zephyr_sys::syscalls::$context::k_msgq_put(
+                    msgq as *const _ as *mut _,
+                    data as _,
+                    timeout,
+                )
+            }
+
+            unsafe fn k_msgq_get(msgq: &k_msgq, data: *mut c_void, timeout: k_timeout_t) -> c_int {
+                zephyr_sys::syscalls::$context::k_msgq_get(
+                    msgq as *const _ as *mut _,
+                    data,
+                    timeout,
+                )
+            }
+
+            unsafe fn k_msgq_peek(msgq: &k_msgq, data: *mut c_void) -> c_int {
+                zephyr_sys::syscalls::$context::k_msgq_peek(msgq as *const _ as *mut _, data)
+            }
+
+            fn k_msgq_purge(msgq: &k_msgq) {
+                unsafe { zephyr_sys::syscalls::$context::k_msgq_purge(msgq as *const _ as *mut _) }
+            }
+
+            fn k_msgq_num_free_get(msgq: &k_msgq) -> u32 {
+                unsafe {
+                    zephyr_sys::syscalls::$context::k_msgq_num_free_get(msgq as *const _ as *mut _)
+                }
+            }
+
+            fn k_msgq_num_used_get(msgq: &k_msgq) -> u32 {
+                unsafe {
+                    zephyr_sys::syscalls::$context::k_msgq_num_used_get(msgq as *const _ as *mut _)
+                }
+            }
+        }
+    };
+}
+
+trait_impl!(kernel, crate::context::Kernel);
+trait_impl!(user, crate::context::User);
+trait_impl!(any, crate::context::Any);
+
+/// Static backing storage for a message queue holding `N` messages of type `T`
+pub struct MsgqBuffer<T, const N: usize>(UnsafeCell<[MaybeUninit<T>; N]>);
+
+unsafe impl<T: Send, const N: usize> Sync for MsgqBuffer<T, N> {}
+
+impl<T: Copy, const N: usize> MsgqBuffer<T, N> {
+    /// Evaluated at compile time when a queue is bound to a buffer
+    const VALID: () = {
+        assert!(
+            mem::size_of::<T>() != 0,
+            "message type must not be zero sized"
+        );
+        assert!(N != 0, "message queue depth must not be zero");
+        assert!(
+            N <= u32::MAX as usize,
+            "message queue depth must fit in u32"
+        );
+    };
+
+    pub const fn new() -> Self {
+        MsgqBuffer(UnsafeCell::new([MaybeUninit::uninit(); N]))
+    }
+}
+
+/// Kernel-only initialization of a statically allocated queue
+pub trait RawMsgq {
+    /// Unsafe because the queue must not be in use and the buffer must not be shared with any
+    /// other queue.
+    unsafe fn init<T: Copy, const N: usize>(&self, buffer: &'static MsgqBuffer<T, N>);
+}
+
+impl RawMsgq for KMsgq {
+    unsafe fn init<T: Copy, const N: usize>(&self, buffer: &'static MsgqBuffer<T, N>) {
+        let _ = MsgqBuffer::<T, N>::VALID;
+        // The buffer is an array of T, so each message slot is aligned for T
+        zephyr_sys::raw::k_msgq_init(
+            self as *const _ as *mut _,
+            buffer.0.get() as *mut _,
+            mem::size_of::<T>(),
+            N as u32,
+        );
+    }
+}
+
+/// Typed message queue over a k_msgq
+///
+/// Messages are copied in and out of the queue's buffer by the kernel, hence `T: Copy`.
+pub struct MsgQueue<'q, T: Copy> {
+    msgq: &'q KMsgq,
+    _msg: PhantomData<T>,
+}
+
+unsafe impl<'q, T: Copy + Send> Send for MsgQueue<'q, T> {}
+unsafe impl<'q, T: Copy + Send> Sync for MsgQueue<'q, T> {}
+
+impl<'q, T: Copy> MsgQueue<'q, T> {
+    const VALID: () = assert!(
+        mem::size_of::<T>() != 0,
+        "message type must not be zero sized"
+    );
+
+    /// Unsafe because the caller must ensure the queue is initialized with a message size of
+    /// `size_of::<T>()` and a buffer aligned for `T`.
+    pub const unsafe fn new(msgq: &'q KMsgq) -> Self {
+        let _ = Self::VALID;
+        MsgQueue {
+            msgq,
+            _msg: PhantomData,
+        }
+    }
+
+    /// Get the kernel object, e.g. to grant a thread access
+    pub fn kobj(&self) -> &'q KMsgq {
+        self.msgq
+    }
+
+    fn put_raw<C: MsgqSyscalls>(&self, msg: &T, timeout: k_timeout_t) -> Result<()> {
+        unsafe { C::k_msgq_put(self.msgq, msg as *const T as *const c_void, timeout) }
+            .neg_err()
+            .map(|_| ())
+    }
+
+    fn get_raw<C: MsgqSyscalls>(&self, timeout: k_timeout_t) -> Result<T> {
+        let mut msg = MaybeUninit::<T>::uninit();
+        unsafe { C::k_msgq_get(self.msgq, msg.as_mut_ptr() as *mut c_void, timeout) }
+            .neg_err()
+            .map(|_| unsafe { msg.assume_init() })
+    }
+
+    /// Put with infinite timeout. Fails with `NoMessage` if the queue is purged while waiting.
+    pub fn put<C: MsgqSyscalls>(&self, msg: &T) -> Result<()> {
+        self.put_raw::<C>(msg, zephyr_sys::raw::K_FOREVER)
+    }
+
+    /// Put with timeout. Fails with `Again` on timeout, or `NoMessage` if the queue is full and
+    /// the timeout is `K_NO_WAIT` or the queue is purged while waiting.
+    pub fn put_timeout<C: MsgqSyscalls>(&self, msg: &T, timeout: Timeout) -> Result<()> {
+        self.put_raw::<C>(msg, timeout.0)
+    }
+
+    /// Put with no timeout. Fails with `NoMessage` if the queue is full.
+    pub fn try_put<C: MsgqSyscalls>(&self, msg: &T) -> Result<()> {
+        self.put_raw::<C>(msg, zephyr_sys::raw::K_NO_WAIT)
+    }
+
+    /// Get with infinite timeout
+    pub fn get<C: MsgqSyscalls>(&self) -> Result<T> {
+        self.get_raw::<C>(zephyr_sys::raw::K_FOREVER)
+    }
+
+    /// Get with timeout. Fails with `Again` on timeout, or `NoMessage` if the queue is empty and
+    /// the timeout is `K_NO_WAIT`.
+    pub fn get_timeout<C: MsgqSyscalls>(&self, timeout: Timeout) -> Result<T> {
+        self.get_raw::<C>(timeout.0)
+    }
+
+    /// Get with no timeout. Fails with `NoMessage` if the queue is empty.
+    pub fn try_get<C: MsgqSyscalls>(&self) -> Result<T> {
+        self.get_raw::<C>(zephyr_sys::raw::K_NO_WAIT)
+    }
+
+    /// Read the message at the head of the queue without removing it. Fails with `NoMessage` if
+    /// the queue is empty.
+    pub fn peek<C: MsgqSyscalls>(&self) -> Result<T> {
+        let mut msg = MaybeUninit::<T>::uninit();
+        unsafe { C::k_msgq_peek(self.msgq, msg.as_mut_ptr() as *mut c_void) }
+            .neg_err()
+            .map(|_| unsafe { msg.assume_init() })
+    }
+
+    /// Discard all messages. Threads blocked in put are woken and fail.
+    pub fn purge<C: MsgqSyscalls>(&self) {
+        C::k_msgq_purge(self.msgq)
+    }
+
+    pub fn num_free<C: MsgqSyscalls>(&self) -> u32 {
+        C::k_msgq_num_free_get(self.msgq)
+    }
+
+    pub fn num_used<C: MsgqSyscalls>(&self) -> u32 {
+        C::k_msgq_num_used_get(self.msgq)
+    }
+}
+
+impl<'q, T: Copy> Clone for MsgQueue<'q, T> {
+    fn clone(&self) -> Self {
+        MsgQueue {
+            msgq: self.msgq,
+            _msg: PhantomData,
+        }
+    }
+}
@@ -5,7 +5,9 @@ use core::ops::{Deref, DerefMut};
 use zephyr_sys::raw::{k_mutex, k_objects};
 
 use super::NegErr;
+use crate::error::Result;
 use crate::kobj::*;
+use crate::time::Timeout;
 
 // Declare the Zephyr struct to be a kernel object
 unsafe impl KObj for k_mutex {
@@ -16,6 +18,18 @@ pub use zephyr_sys::raw::k_mutex as KMutex;
 
 crate::make_static_wrapper!(k_mutex, zephyr_sys::raw::k_mutex);
 
+#[cfg(static_kobj_init)]
+impl global::k_mutex {
+    /// Equivalent of Z_MUTEX_INITIALIZER, for a static at address `this`
+    pub const unsafe fn initializer(this: *const Self) -> Self {
+        let this = this as *const k_mutex;
+        let mut mutex: k_mutex = core::mem::zeroed();
+        mutex.wait_q = wait_q_init(core::ptr::addr_of!((*this).wait_q));
+        mutex.owner_orig_prio = zephyr_sys::raw::RUST_K_LOWEST_APPLICATION_THREAD_PRIO as _;
+        Self::new(mutex)
+    }
+}
+
 /// Raw syscall API
 pub trait MutexSyscalls {
     unsafe fn k_mutex_init(mutex: *mut zephyr_sys::raw::k_mutex);
@@ -23,7 +37,7 @@ pub trait MutexSyscalls {
         mutex: *mut zephyr_sys::raw::k_mutex,
         timeout: zephyr_sys::raw::k_timeout_t,
     ) -> libc::c_int;
-    unsafe fn k_mutex_unlock(mutex: *mut zephyr_sys::raw::k_mutex);
+    unsafe fn k_mutex_unlock(mutex: *mut zephyr_sys::raw::k_mutex) -> libc::c_int;
 }
 
 /// Safer API implemented for the mutex kobject.
@@ -31,9 +45,13 @@ pub trait MutexSyscalls {
 /// Still not safe because it doesn't implement a lock guard.
 pub trait RawMutex {
     unsafe fn init<C: MutexSyscalls>(self);
-    unsafe fn lock<C: MutexSyscalls>(self);
-    unsafe fn unlock<C: MutexSyscalls>(self);
-    unsafe fn try_lock<C: MutexSyscalls>(self) -> bool;
+    unsafe fn lock<C: MutexSyscalls>(self) -> Result<()>;
+    /// Fails with `Again` on timeout, or `Busy` if the timeout is `K_NO_WAIT`
+    unsafe fn lock_timeout<C: MutexSyscalls>(self, timeout: Timeout) -> Result<()>;
+    /// Fails with `NotPermitted` if locked by another thread, or `InvalidArgument` if not locked
+    unsafe fn unlock<C: MutexSyscalls>(self) -> Result<()>;
+    /// Fails with `Busy` if locked by another thread
+    unsafe fn try_lock<C: MutexSyscalls>(self) -> Result<()>;
 }
 
 impl<'a> RawMutex for &'a KMutex {
@@ -42,23 +60,24 @@ impl<'a> RawMutex for &'a KMutex {
         C::k_mutex_init(self as *const _ as *mut _)
     }
 
-    unsafe fn lock<C: MutexSyscalls>(self) {
-        C::k_mutex_lock(self as *const _ as *mut _, zephyr_sys::raw::K_FOREVER)
+    unsafe fn lock<C: MutexSyscalls>(self) -> Result<()> {
+        self.lock_timeout::<C>(crate::K_FOREVER)
+    }
+
+    unsafe fn lock_timeout<C: MutexSyscalls>(self, timeout: Timeout) -> Result<()> {
+        C::k_mutex_lock(self as *const _ as *mut _, timeout.0)
             .neg_err()
-            .expect("mutex lock");
+            .map(|_| ())
     }
 
-    unsafe fn unlock<C: MutexSyscalls>(self) {
-        C::k_mutex_unlock(self as *const _ as *mut _);
+    unsafe fn unlock<C: MutexSyscalls>(self) -> Result<()> {
+        C::k_mutex_unlock(self as *const _ as *mut _)
+            .neg_err()
+            .map(|_| ())
     }
 
-    unsafe fn try_lock<C: MutexSyscalls>(self) -> bool {
-        match C::k_mutex_lock(self as *const _ as *mut _, zephyr_sys::raw::K_NO_WAIT).neg_err() {
-            Ok(_) => Ok(true),
-            Err(zephyr_sys::raw::EBUSY) => Ok(false),
-            Err(e) => Err(e),
-        }
-        .expect("mutex try_lock")
+    unsafe fn try_lock<C: MutexSyscalls>(self) -> Result<()> {
+        self.lock_timeout::<C>(crate::K_NO_WAIT)
     }
 }
 
@@ -84,14 +103,29 @@ impl<'m, T> Mutex<'m, T> {
         self.mutex as *const _ as *mut _
     }
 
-    pub fn lock<'a, C: MutexSyscalls>(&'a self) -> MutexGuard<'a, T, C> {
+    pub fn lock<'a, C: MutexSyscalls>(&'a self) -> Result<MutexGuard<'a, T, C>> {
+        self.lock_timeout(crate::K_FOREVER)
+    }
+
+    /// Lock if not already locked by another thread
+    pub fn try_lock<'a, C: MutexSyscalls>(&'a self) -> Option<MutexGuard<'a, T, C>> {
+        self.lock_timeout(crate::K_NO_WAIT).ok()
+    }
+
+    /// Lock, waiting up to `timeout` for another thread to unlock it. Fails with `Again` on
+    /// timeout, or `Busy` if the timeout is `K_NO_WAIT`.
+    pub fn lock_timeout<'a, C: MutexSyscalls>(
+        &'a self,
+        timeout: Timeout,
+    ) -> Result<MutexGuard<'a, T, C>> {
         unsafe {
-            self.mutex.lock::<C>();
+            self.mutex.lock_timeout::<C>(timeout)?;
         }
-        MutexGuard {
+        Ok(MutexGuard {
             mutex: self,
             _syscalls: PhantomData,
-        }
+            _not_send: PhantomData,
+        })
     }
 }
 
@@ -106,14 +140,33 @@ impl<'m, 'd, T> Clone for Mutex<'m, &'d T> {
     }
 }
 
+/// Not `Send`, as only the thread that locked a k_mutex can unlock it
 pub struct MutexGuard<'a, T: 'a, C: MutexSyscalls> {
     mutex: &'a Mutex<'a, T>,
     _syscalls: PhantomData<C>,
+    _not_send: PhantomData<*const ()>,
+}
+
+#[cfg(zephyr250)]
+impl<'a, T: 'a, C: MutexSyscalls> MutexGuard<'a, T, C> {
+    /// The kernel object held by this guard, for waiting on a condition variable
+    pub(crate) fn kmutex(&self) -> &'a KMutex {
+        self.mutex.mutex
+    }
+}
+
+impl<'a, T: 'a, C: MutexSyscalls> MutexGuard<'a, T, C> {
+    /// Unlock, returning the error that drop ignores
+    pub fn unlock(guard: Self) -> Result<()> {
+        let guard = core::mem::ManuallyDrop::new(guard);
+        unsafe { guard.mutex.mutex.unlock::<C>() }
+    }
 }
 
 impl<'a, T: 'a, C: MutexSyscalls> Drop for MutexGuard<'a, T, C> {
     fn drop(&mut self) {
-        unsafe { self.mutex.mutex.unlock::<C>() }
+        // Use `MutexGuard::unlock` to see the error
+        let _ = unsafe { self.mutex.mutex.unlock::<C>() };
     }
 }
 
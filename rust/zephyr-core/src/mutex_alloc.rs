@@ -3,12 +3,16 @@ use alloc::boxed::Box;
 use core::ops::Deref;
 use core::ptr::NonNull;
 
+#[cfg(mutex_pool)]
+use crate::error::Error;
+use crate::error::Result;
 use crate::mutex::*;
 
 pub struct DynMutex(NonNull<KMutex>);
 
 impl DynMutex {
-    pub fn new<C: MutexSyscalls>() -> Option<Self> {
+    /// Fails with `NoMemory` if the mutex pool is exhausted
+    pub fn new<C: MutexSyscalls>() -> Result<Self> {
         unsafe {
             #[cfg(not(mutex_pool))]
             let m = {
@@ -17,9 +21,9 @@ impl DynMutex {
                 m
             };
             #[cfg(mutex_pool)]
-            let m = mutex_pool::alloc_mutex().expect("mutex pool exhausted");
+            let m = mutex_pool::alloc_mutex().ok_or(Error::NoMemory)?;
 
-            Some(DynMutex(NonNull::new_unchecked(m)))
+            Ok(DynMutex(NonNull::new_unchecked(m)))
         }
     }
 
@@ -0,0 +1,147 @@
+//! Reporting panics and raising a Zephyr fatal error
+//!
+//! A panic is printed with k_str_out, with its location and the name of the current thread, then
+//! raised as a kernel oops so the fatal error handler, coredump and reboot policy apply. The
+//! `zephyr` crate installs this as the std panic hook. An app without std can use it as its panic
+//! handler:
+//!
+//! ```ignore
+//! #[panic_handler]
+//! fn panic(info: &core::panic::PanicInfo) -> ! {
+//!     zephyr_core::panic::handle(info)
+//! }
+//! ```
+//!
+//! A hook for custom reporting, such as saving the message to flash, runs after the report:
+//!
+//! ```ignore
+//! fn save_panic(info: &core::panic::PanicInfo) {}
+//!
+//! zephyr::panic::set_hook(save_panic);
+//! ```
+
+use alloc::string::String;
+use core::fmt::{self, Write};
+use core::panic::PanicInfo;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use crate::thread::ThreadSyscalls;
+
+/// Function called with a panic after it is reported
+pub type Hook = fn(&PanicInfo);
+
+/// Fatal error raised after a panic is reported
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Fatal {
+    /// `k_oops`. The kernel aborts the panicking thread, or halts if it is essential or the
+    /// panic is in an ISR.
+    Oops,
+    /// `k_panic`, which halts the system. Raised as an oops from user mode.
+    Panic,
+}
+
+static HOOK: AtomicUsize = AtomicUsize::new(0);
+static FATAL: AtomicBool = AtomicBool::new(false);
+/// Thread reporting a panic, or 0
+static PANICKING: AtomicUsize = AtomicUsize::new(0);
+
+/// Register a hook called with every panic after it is printed, before the fatal error. Replaces
+/// any previous hook.
+pub fn set_hook(hook: Hook) {
+    HOOK.store(hook as usize, Ordering::Release);
+}
+
+/// Unregister the hook, returning it
+pub fn take_hook() -> Option<Hook> {
+    match HOOK.swap(0, Ordering::Acquire) {
+        0 => None,
+        hook => Some(unsafe { core::mem::transmute::<usize, Hook>(hook) }),
+    }
+}
+
+/// Set the fatal error raised after a panic. Defaults to `Fatal::Oops`.
+pub fn set_fatal(fatal: Fatal) {
+    FATAL.store(fatal == Fatal::Panic, Ordering::Relaxed);
+}
+
+/// Report a panic, call the hook, then raise the fatal error
+///
+/// A panic while reporting, such as from the hook, goes straight to the fatal error.
+pub fn handle(info: &PanicInfo) -> ! {
+    let current = crate::context::Any::k_current_get().tid() as usize;
+    match PANICKING.compare_exchange(0, current, Ordering::AcqRel, Ordering::Acquire) {
+        // Already reporting on this thread
+        Err(thread) if thread == current => {}
+        // Reported even if another thread is reporting, as that thread may never finish
+        _ => {
+            report(info);
+            let hook = HOOK.load(Ordering::Acquire);
+            if hook != 0 {
+                let hook = unsafe { core::mem::transmute::<usize, Hook>(hook) };
+                hook(info);
+            }
+        }
+    }
+    // The fatal error may only abort this thread, and the next panic on it must be reported
+    let _ = PANICKING.compare_exchange(current, 0, Ordering::Release, Ordering::Relaxed);
+    fatal()
+}
+
+/// Print a panic's message and location and the current thread with k_str_out
+pub fn report(info: &PanicInfo) {
+    let mut out = StrOut;
+    let _ = write!(out, "Rust panic in thread ");
+    let current = crate::context::Any::k_current_get();
+    let mut name = [0u8; 32];
+    match current.k_thread_name_copy::<crate::context::Any>(&mut name) {
+        Ok(name) if !name.is_empty() => {
+            let _ = write!(out, "'{}'", name);
+        }
+        _ => {
+            let _ = write!(out, "{:p}", current.tid());
+        }
+    }
+    if let Some(location) = info.location() {
+        let _ = write!(
+            out,
+            " at {}:{}:{}",
+            location.file(),
+            location.line(),
+            location.column()
+        );
+    }
+    // core's panic handler only has a message. std's hook has a payload instead for panics with
+    // a single string argument or panic_any.
+    let _ = if let Some(message) = info.message() {
+        writeln!(out, ": {}", message)
+    } else if let Some(message) = info.payload().downcast_ref::<&str>() {
+        writeln!(out, ": {}", message)
+    } else if let Some(message) = info.payload().downcast_ref::<String>() {
+        writeln!(out, ": {}", message)
+    } else {
+        writeln!(out)
+    };
+}
+
+/// Raise the fatal error set with `set_fatal`
+pub fn fatal() -> ! {
+    unsafe {
+        if FATAL.load(Ordering::Relaxed) {
+            zephyr_sys::raw::rust_k_panic();
+        } else {
+            zephyr_sys::raw::rust_k_oops();
+        }
+    }
+    // The wrappers do not return, but bindgen does not know that
+    loop {}
+}
+
+/// k_str_out, which works from any context, including user mode and ISRs
+struct StrOut;
+
+impl Write for StrOut {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        crate::any::k_str_out_raw(s.as_bytes());
+        Ok(())
+    }
+}
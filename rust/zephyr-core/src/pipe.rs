@@ -0,0 +1,227 @@
+use core::cell::UnsafeCell;
+
+use libc::{c_int, c_void};
+use zephyr_sys::raw::{k_objects, k_pipe, k_timeout_t};
+
+use super::NegErr;
+use crate::error::Result;
+use crate::kobj::*;
+use crate::time::Timeout;
+
+// Declare the Zephyr struct to be a kernel object
+unsafe impl KObj for k_pipe {
+    const OTYPE: k_objects = zephyr_sys::raw::k_objects_K_OBJ_PIPE;
+}
+
+pub use zephyr_sys::raw::k_pipe as KPipe;
+
+crate::make_static_wrapper!(k_pipe, zephyr_sys::raw::k_pipe);
+
+/// Raw syscall API
+pub trait PipeSyscalls {
+    /// Allocates the buffer from the calling thread's resource pool
+    unsafe fn k_pipe_alloc_init(pipe: &k_pipe, size: usize) -> c_int;
+    unsafe fn k_pipe_put(
+        pipe: &k_pipe,
+        data: *const c_void,
+        bytes_to_write: usize,
+        bytes_written: &mut usize,
+        min_xfer: usize,
+        timeout: k_timeout_t,
+    ) -> c_int;
+    unsafe fn k_pipe_get(
+        pipe: &k_pipe,
+        data: *mut c_void,
+        bytes_to_read: usize,
+        bytes_read: &mut usize,
+        min_xfer: usize,
+        timeout: k_timeout_t,
+    ) -> c_int;
+    #[cfg(zephyr250)]
+    fn k_pipe_read_avail(pipe: &k_pipe) -> usize;
+    #[cfg(zephyr250)]
+    fn k_pipe_write_avail(pipe: &k_pipe) -> usize;
+}
+
+macro_rules! trait_impl {
+    ($context:ident, $context_struct:path) => {
+        impl PipeSyscalls for $context_struct {
+            unsafe fn k_pipe_alloc_init(pipe: &k_pipe, size: usize) -> c_int {
+                zephyr_sys::syscalls::$context::k_pipe_alloc_init(pipe as *const _ as *mut _, size)
+            }
+
+            unsafe fn k_pipe_put(
+                pipe: &k_pipe,
+                data: *const c_void,
+                bytes_to_write: usize,
+                bytes_written: &mut usize,
+                min_xfer: usize,
+                timeout: k_timeout_t,
+            ) -> c_int {
+                zephyr_sys::syscalls::$context::k_pipe_put(
+                    pipe as *const _ as *mut _,
+                    data as _,
+                    bytes_to_write,
+                    bytes_written,
+                    min_xfer,
+                    timeout,
+                )
+            }
+
+            unsafe fn k_pipe_get(
+                pipe: &k_pipe,
+                data: *mut c_void,
+                bytes_to_read: usize,
+                bytes_read: &mut usize,
+                min_xfer: usize,
+                timeout: k_timeout_t,
+            ) -> c_int {
+                zephyr_sys::syscalls::$context::k_pipe_get(
+                    pipe as *const _ as *mut _,
+                    data,
+                    bytes_to_read,
+                    bytes_read,
+                    min_xfer,
+                    timeout,
+                )
+            }
+
+            #[cfg(zephyr250)]
+            fn k_pipe_read_avail(pipe: &k_pipe) -> usize {
+                unsafe {
+                    zephyr_sys::syscalls::$context::k_pipe_read_avail(pipe as *const _ as *mut _)
+                }
+            }
+
+            #[cfg(zephyr250)]
+            fn k_pipe_write_avail(pipe: &k_pipe) -> usize {
+                unsafe {
+                    zephyr_sys::syscalls::$context::k_pipe_write_avail(pipe as *const _ as *mut _)
+                }
+            }
+        }
+    };
+}
+
+trait_impl!(kernel, crate::context::Kernel);
+trait_impl!(user, crate::context::User);
+trait_impl!(any, crate::context::Any);
+
+/// Static ring buffer storage for a pipe
+pub struct PipeBuffer<const N: usize>(UnsafeCell<[u8; N]>);
+
+unsafe impl<const N: usize> Sync for PipeBuffer<N> {}
+
+impl<const N: usize> PipeBuffer<N> {
+    pub const fn new() -> Self {
+        PipeBuffer(UnsafeCell::new([0; N]))
+    }
+}
+
+/// Safe API implemented on the pipe struct. Converts errors.
+///
+/// Reads and writes transfer at least one byte unless they time out, so they behave like a
+/// stream rather than a message queue.
+pub trait Pipe {
+    /// Kernel-only initialization of a statically allocated pipe. Unsafe because the pipe must
+    /// not be in use and the buffer must not be shared with any other pipe.
+    unsafe fn init<const N: usize>(&self, buffer: &'static PipeBuffer<N>);
+    /// Blocking read (lower level than `std::io::Read`). Waits until at least one byte is
+    /// available.
+    fn read<C: PipeSyscalls>(&self, buf: &mut [u8]) -> Result<usize>;
+    /// Non blocking read. Fails with `Io` if nothing could be read.
+    fn read_nb<C: PipeSyscalls>(&self, buf: &mut [u8]) -> Result<usize>;
+    /// Read with timeout. Fails with `Again` if nothing was read before the timeout.
+    fn read_timeout<C: PipeSyscalls>(&self, buf: &mut [u8], timeout: Timeout) -> Result<usize>;
+    /// Blocking write (lower level than `std::io::Write`). Waits until at least one byte is
+    /// written.
+    fn write<C: PipeSyscalls>(&self, buf: &[u8]) -> Result<usize>;
+    /// Non blocking write. Fails with `Io` if nothing could be written.
+    fn write_nb<C: PipeSyscalls>(&self, buf: &[u8]) -> Result<usize>;
+    /// Write with timeout. Fails with `Again` if nothing was written before the timeout.
+    fn write_timeout<C: PipeSyscalls>(&self, buf: &[u8], timeout: Timeout) -> Result<usize>;
+    /// Number of bytes that can be read without blocking
+    #[cfg(zephyr250)]
+    fn read_avail<C: PipeSyscalls>(&self) -> usize;
+    /// Number of bytes that can be written without blocking
+    #[cfg(zephyr250)]
+    fn write_avail<C: PipeSyscalls>(&self) -> usize;
+}
+
+fn get<C: PipeSyscalls>(pipe: &KPipe, buf: &mut [u8], timeout: k_timeout_t) -> Result<usize> {
+    if buf.is_empty() {
+        return Ok(0);
+    }
+    let mut bytes_read = 0;
+    unsafe {
+        C::k_pipe_get(
+            pipe,
+            buf.as_mut_ptr() as *mut _,
+            buf.len(),
+            &mut bytes_read,
+            1,
+            timeout,
+        )
+    }
+    .neg_err()
+    .map(|_| bytes_read)
+}
+
+fn put<C: PipeSyscalls>(pipe: &KPipe, buf: &[u8], timeout: k_timeout_t) -> Result<usize> {
+    if buf.is_empty() {
+        return Ok(0);
+    }
+    let mut bytes_written = 0;
+    unsafe {
+        C::k_pipe_put(
+            pipe,
+            buf.as_ptr() as *const _,
+            buf.len(),
+            &mut bytes_written,
+            1,
+            timeout,
+        )
+    }
+    .neg_err()
+    .map(|_| bytes_written)
+}
+
+impl Pipe for KPipe {
+    unsafe fn init<const N: usize>(&self, buffer: &'static PipeBuffer<N>) {
+        zephyr_sys::raw::k_pipe_init(self as *const _ as *mut _, buffer.0.get() as *mut _, N);
+    }
+
+    fn read<C: PipeSyscalls>(&self, buf: &mut [u8]) -> Result<usize> {
+        get::<C>(self, buf, zephyr_sys::raw::K_FOREVER)
+    }
+
+    fn read_nb<C: PipeSyscalls>(&self, buf: &mut [u8]) -> Result<usize> {
+        get::<C>(self, buf, zephyr_sys::raw::K_NO_WAIT)
+    }
+
+    fn read_timeout<C: PipeSyscalls>(&self, buf: &mut [u8], timeout: Timeout) -> Result<usize> {
+        get::<C>(self, buf, timeout.0)
+    }
+
+    fn write<C: PipeSyscalls>(&self, buf: &[u8]) -> Result<usize> {
+        put::<C>(self, buf, zephyr_sys::raw::K_FOREVER)
+    }
+
+    fn write_nb<C: PipeSyscalls>(&self, buf: &[u8]) -> Result<usize> {
+        put::<C>(self, buf, zephyr_sys::raw::K_NO_WAIT)
+    }
+
+    fn write_timeout<C: PipeSyscalls>(&self, buf: &[u8], timeout: Timeout) -> Result<usize> {
+        put::<C>(self, buf, timeout.0)
+    }
+
+    #[cfg(zephyr250)]
+    fn read_avail<C: PipeSyscalls>(&self) -> usize {
+        C::k_pipe_read_avail(self)
+    }
+
+    #[cfg(zephyr250)]
+    fn write_avail<C: PipeSyscalls>(&self) -> usize {
+        C::k_pipe_write_avail(self)
+    }
+}
@@ -1,9 +1,12 @@
 use libc::{c_int, c_void};
 use zephyr_sys::raw::{
-    _poll_types_bits__POLL_TYPE_SEM_AVAILABLE, _poll_types_bits__POLL_TYPE_SIGNAL, k_poll_event,
-    k_poll_modes_K_POLL_MODE_NOTIFY_ONLY, k_timeout_t, K_POLL_STATE_NOT_READY, K_POLL_TYPE_IGNORE,
+    _poll_types_bits__POLL_TYPE_DATA_AVAILABLE, _poll_types_bits__POLL_TYPE_SEM_AVAILABLE,
+    _poll_types_bits__POLL_TYPE_SIGNAL, k_poll_event, k_poll_modes_K_POLL_MODE_NOTIFY_ONLY,
+    k_timeout_t, K_POLL_STATE_NOT_READY, K_POLL_TYPE_IGNORE,
 };
 
+use crate::error::{Error, Result};
+use crate::fifo::KQueue;
 use crate::kobj::*;
 use crate::semaphore::KSem;
 use crate::time::Timeout;
@@ -25,6 +28,10 @@ unsafe impl PollableKobj for KPollSignal {
     const POLL_TYPE: u32 = 1 << (_poll_types_bits__POLL_TYPE_SIGNAL - 1);
 }
 
+unsafe impl PollableKobj for KQueue {
+    const POLL_TYPE: u32 = 1 << (_poll_types_bits__POLL_TYPE_DATA_AVAILABLE - 1);
+}
+
 #[repr(u32)]
 pub enum PollMode {
     NotifyOnly = k_poll_modes_K_POLL_MODE_NOTIFY_ONLY,
@@ -98,41 +105,27 @@ trait_impl!(kernel, crate::context::Kernel);
 trait_impl!(user, crate::context::User);
 trait_impl!(any, crate::context::Any);
 
-#[derive(Clone, Copy, Debug)]
-pub enum PollError {
-    Canceled,
-}
-
 pub trait PollEventsFuncs {
-    fn poll<C: PollSyscalls>(&mut self) -> Result<(), PollError>;
+    /// Wait with infinite timeout. Fails with `Interrupted` if a polled queue's wait is
+    /// cancelled, or `NoMemory` if the kernel runs out of memory to wait on the events.
+    fn poll<C: PollSyscalls>(&mut self) -> Result<()>;
     /// Returns true if events are ready, false if timeout.
-    fn poll_timeout<C: PollSyscalls>(
-        &mut self,
-        timeout: Option<Timeout>,
-    ) -> Result<bool, PollError>;
+    fn poll_timeout<C: PollSyscalls>(&mut self, timeout: Option<Timeout>) -> Result<bool>;
 }
 
 impl PollEventsFuncs for [KPollEvent] {
-    fn poll<C: PollSyscalls>(&mut self) -> Result<(), PollError> {
-        match C::k_poll(self, zephyr_sys::raw::K_FOREVER).neg_err() {
-            Ok(_) => Ok(()),
-            Err(zephyr_sys::raw::EINTR) => Err(PollError::Canceled),
-            Err(zephyr_sys::raw::ENOMEM) => panic!("k_poll OOM"),
-            Err(e) => panic!("k_poll error {}", e),
-        }
+    fn poll<C: PollSyscalls>(&mut self) -> Result<()> {
+        C::k_poll(self, zephyr_sys::raw::K_FOREVER)
+            .neg_err()
+            .map(|_| ())
     }
 
-    fn poll_timeout<C: PollSyscalls>(
-        &mut self,
-        timeout: Option<Timeout>,
-    ) -> Result<bool, PollError> {
+    fn poll_timeout<C: PollSyscalls>(&mut self, timeout: Option<Timeout>) -> Result<bool> {
         let timeout = timeout.map(|x| x.0).unwrap_or(zephyr_sys::raw::K_FOREVER);
         match C::k_poll(self, timeout).neg_err() {
             Ok(_) => Ok(true),
-            Err(zephyr_sys::raw::EAGAIN) => Ok(false),
-            Err(zephyr_sys::raw::EINTR) => Err(PollError::Canceled),
-            Err(zephyr_sys::raw::ENOMEM) => panic!("k_poll OOM"),
-            Err(e) => panic!("k_poll error {}", e),
+            Err(Error::Again) => Ok(false),
+            Err(e) => Err(e),
         }
     }
 }
@@ -11,6 +11,17 @@ unsafe impl KObj for KPollSignal {
 
 crate::make_static_wrapper!(k_poll_signal, zephyr_sys::raw::k_poll_signal);
 
+#[cfg(static_kobj_init)]
+impl global::k_poll_signal {
+    /// Equivalent of K_POLL_SIGNAL_INITIALIZER, for a static at address `this`
+    pub const unsafe fn initializer(this: *const Self) -> Self {
+        let this = this as *const KPollSignal;
+        let mut signal: KPollSignal = core::mem::zeroed();
+        signal.poll_events = dlist_init(core::ptr::addr_of!((*this).poll_events));
+        Self::new(signal)
+    }
+}
+
 pub trait KPollSignalSyscalls {
     unsafe fn k_poll_signal_init(signal: &KPollSignal);
     fn k_poll_signal_reset(signal: &KPollSignal);
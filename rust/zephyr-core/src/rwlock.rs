@@ -0,0 +1,287 @@
+//! Reader-writer lock built from a kernel mutex and semaphore
+//!
+//! The lock prefers writers. A writer holds the mutex while it waits for readers to leave and
+//! while it holds the lock, and readers pass through the mutex to get in, so no new reader gets in
+//! once a writer is waiting. Waiting writers and readers boost the writer through the mutex's
+//! priority inheritance. Readers already holding the lock are not boosted, since the writer waits
+//! for them on a semaphore.
+//!
+//! The reader count lives in the `RawRwLock` itself, so user threads need access to the memory
+//! it is in as well as to its kernel objects. `k_rwlock_define!` puts it in the Rust partition.
+
+use core::cell::UnsafeCell;
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
+use core::ptr;
+use core::sync::atomic::{AtomicPtr, AtomicU32, Ordering};
+
+use crate::error::{Error, Result};
+use crate::mutex::{KMutex, MutexSyscalls, RawMutex};
+use crate::semaphore::{KSem, Semaphore, SemaphoreSyscalls};
+use crate::thread::{ThreadId, ThreadSyscalls};
+use crate::time::{Ticks, Timeout};
+
+/// Syscalls needed by the lock
+pub trait RwLockSyscalls: MutexSyscalls + SemaphoreSyscalls + ThreadSyscalls {}
+
+impl<C: MutexSyscalls + SemaphoreSyscalls + ThreadSyscalls> RwLockSyscalls for C {}
+
+/// End of a timed wait made of more than one kernel call
+struct Deadline {
+    timeout: Timeout,
+    end: Option<Ticks>,
+}
+
+impl Deadline {
+    fn new(timeout: Timeout) -> Self {
+        let end = if timeout.0.ticks == crate::K_FOREVER.0.ticks {
+            None
+        } else {
+            Some(Ticks(crate::any::k_uptime_ticks().0 + timeout.0.ticks))
+        };
+        Deadline { timeout, end }
+    }
+
+    fn remaining(&self) -> Timeout {
+        match self.end {
+            None => crate::K_FOREVER,
+            Some(end) => end.sub_timeout(crate::any::k_uptime_ticks()),
+        }
+    }
+
+    /// Waiting on what is left of a timeout can fail with `Busy` once none is left. Report the
+    /// timeout like a single wait would.
+    fn map_err(&self, e: Error) -> Error {
+        match e {
+            Error::Busy if self.timeout.0.ticks != crate::K_NO_WAIT.0.ticks => Error::Again,
+            e => e,
+        }
+    }
+}
+
+/// Lock state without the data, e.g. to share one lock between several pieces of data
+///
+/// Define with `k_rwlock_define!`, which creates and initializes the kernel objects.
+pub struct RawRwLock<'a> {
+    mutex: &'a KMutex,
+    /// Given each time the last reader leaves
+    drained: &'a KSem,
+    readers: AtomicU32,
+    /// Thread holding the write lock, to fail instead of handing a recursive lock to the writer
+    writer: AtomicPtr<zephyr_sys::raw::k_thread>,
+}
+
+impl<'a> RawRwLock<'a> {
+    /// Unsafe because the semaphore must be initialized with a count of 0 and a limit of 1, and
+    /// neither object may be used by anything else.
+    pub const unsafe fn new(mutex: &'a KMutex, drained: &'a KSem) -> Self {
+        RawRwLock {
+            mutex,
+            drained,
+            readers: AtomicU32::new(0),
+            writer: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+
+    /// Grant a thread access to the lock's kernel objects
+    pub fn grant<C: ThreadSyscalls>(&self, thread: ThreadId) {
+        thread.k_object_access_grant::<C, _>(self.mutex);
+        thread.k_object_access_grant::<C, _>(self.drained);
+    }
+
+    fn is_writer<C: RwLockSyscalls>(&self) -> bool {
+        self.writer.load(Ordering::Relaxed) == C::k_current_get().tid()
+    }
+
+    /// Take a read lock. Fails with `Again` on timeout, `Busy` if the timeout is `K_NO_WAIT`, or
+    /// `Deadlock` if the current thread holds the write lock.
+    ///
+    /// Unsafe because it must be released with `unlock_read`.
+    pub unsafe fn read_timeout<C: RwLockSyscalls>(&self, timeout: Timeout) -> Result<()> {
+        self.mutex.lock_timeout::<C>(timeout)?;
+        // The mutex is recursive, so the writer gets through it
+        let ret = if self.is_writer::<C>() {
+            Err(Error::Deadlock)
+        } else {
+            self.readers.fetch_add(1, Ordering::Acquire);
+            Ok(())
+        };
+        self.mutex.unlock::<C>().expect("rwlock unlock");
+        ret
+    }
+
+    /// Unsafe because the current thread must hold a read lock
+    pub unsafe fn unlock_read<C: RwLockSyscalls>(&self) {
+        if self.readers.fetch_sub(1, Ordering::Release) == 1 {
+            self.drained.give::<C>();
+        }
+    }
+
+    /// Take the write lock. Fails with `Again` on timeout, `Busy` if the timeout is `K_NO_WAIT`
+    /// and the lock is held, or `Deadlock` if the current thread already holds the write lock.
+    /// The timeout covers waiting for both other writers and readers.
+    ///
+    /// Unsafe because it must be released with `unlock_write`.
+    pub unsafe fn write_timeout<C: RwLockSyscalls>(&self, timeout: Timeout) -> Result<()> {
+        let deadline = Deadline::new(timeout);
+        self.mutex.lock_timeout::<C>(timeout)?;
+        if self.is_writer::<C>() {
+            self.mutex.unlock::<C>().expect("rwlock unlock");
+            return Err(Error::Deadlock);
+        }
+        // No reader gets in while the mutex is held, so the count only goes down. The semaphore
+        // is given each time it reaches zero, so a give left over from before only costs another
+        // look at the count.
+        while self.readers.load(Ordering::Acquire) != 0 {
+            if let Err(e) = self.drained.take_timeout::<C>(deadline.remaining()) {
+                self.mutex.unlock::<C>().expect("rwlock unlock");
+                return Err(deadline.map_err(e));
+            }
+        }
+        self.writer
+            .store(C::k_current_get().tid(), Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Fails with `NotPermitted` if the current thread is not the one that took the write lock.
+    ///
+    /// Unsafe because the write lock must be held.
+    pub unsafe fn unlock_write<C: RwLockSyscalls>(&self) -> Result<()> {
+        if !self.is_writer::<C>() {
+            return Err(Error::NotPermitted);
+        }
+        self.writer.store(ptr::null_mut(), Ordering::Relaxed);
+        self.mutex.unlock::<C>()
+    }
+}
+
+/// Safe reader-writer lock container like that in std
+///
+/// Like `Mutex`, using this is safe but creating it is not. A thread that holds a read lock must
+/// not take another while a writer may be waiting, since the writer blocks new readers.
+pub struct RwLock<'l, T> {
+    raw: &'l RawRwLock<'l>,
+    data: UnsafeCell<T>,
+}
+
+unsafe impl<'l, T: Send> Send for RwLock<'l, T> {}
+unsafe impl<'l, T: Send + Sync> Sync for RwLock<'l, T> {}
+
+impl<'l, T> RwLock<'l, T> {
+    /// Unsafe because the raw lock must not protect anything else
+    pub const unsafe fn new(raw: &'l RawRwLock<'l>, data: T) -> Self {
+        RwLock {
+            raw,
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    pub fn raw(&self) -> &'l RawRwLock<'l> {
+        self.raw
+    }
+
+    /// Take a read lock, waiting for any writer to finish
+    pub fn read<C: RwLockSyscalls>(&self) -> Result<RwLockReadGuard<T, C>> {
+        self.read_timeout(crate::K_FOREVER)
+    }
+
+    /// Take a read lock if no writer holds or is waiting for the lock
+    pub fn try_read<C: RwLockSyscalls>(&self) -> Option<RwLockReadGuard<T, C>> {
+        self.read_timeout(crate::K_NO_WAIT).ok()
+    }
+
+    /// Take a read lock, waiting up to `timeout`. Fails like `RawRwLock::read_timeout`.
+    pub fn read_timeout<C: RwLockSyscalls>(
+        &self,
+        timeout: Timeout,
+    ) -> Result<RwLockReadGuard<T, C>> {
+        unsafe { self.raw.read_timeout::<C>(timeout)? };
+        Ok(RwLockReadGuard {
+            lock: self,
+            _syscalls: PhantomData,
+        })
+    }
+
+    /// Take the write lock, waiting for other writers and all readers to finish
+    pub fn write<C: RwLockSyscalls>(&self) -> Result<RwLockWriteGuard<T, C>> {
+        self.write_timeout(crate::K_FOREVER)
+    }
+
+    /// Take the write lock if it is free
+    pub fn try_write<C: RwLockSyscalls>(&self) -> Option<RwLockWriteGuard<T, C>> {
+        self.write_timeout(crate::K_NO_WAIT).ok()
+    }
+
+    /// Take the write lock, waiting up to `timeout`. Fails like `RawRwLock::write_timeout`.
+    pub fn write_timeout<C: RwLockSyscalls>(
+        &self,
+        timeout: Timeout,
+    ) -> Result<RwLockWriteGuard<T, C>> {
+        unsafe { self.raw.write_timeout::<C>(timeout)? };
+        Ok(RwLockWriteGuard {
+            lock: self,
+            _syscalls: PhantomData,
+            _not_send: PhantomData,
+        })
+    }
+
+    /// No locking is needed with a mutable reference
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+}
+
+pub struct RwLockReadGuard<'a, T: 'a, C: RwLockSyscalls> {
+    lock: &'a RwLock<'a, T>,
+    _syscalls: PhantomData<C>,
+}
+
+impl<'a, T: 'a, C: RwLockSyscalls> Drop for RwLockReadGuard<'a, T, C> {
+    fn drop(&mut self) {
+        unsafe { self.lock.raw.unlock_read::<C>() }
+    }
+}
+
+impl<'a, T: 'a, C: RwLockSyscalls> Deref for RwLockReadGuard<'a, T, C> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+/// Not `Send`, as only the thread that took the write lock can release it
+pub struct RwLockWriteGuard<'a, T: 'a, C: RwLockSyscalls> {
+    lock: &'a RwLock<'a, T>,
+    _syscalls: PhantomData<C>,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl<'a, T: 'a, C: RwLockSyscalls> RwLockWriteGuard<'a, T, C> {
+    /// Unlock, returning the error that drop ignores
+    pub fn unlock(guard: Self) -> Result<()> {
+        let guard = core::mem::ManuallyDrop::new(guard);
+        unsafe { guard.lock.raw.unlock_write::<C>() }
+    }
+}
+
+impl<'a, T: 'a, C: RwLockSyscalls> Drop for RwLockWriteGuard<'a, T, C> {
+    fn drop(&mut self) {
+        // Use `RwLockWriteGuard::unlock` to see the error
+        let _ = unsafe { self.lock.raw.unlock_write::<C>() };
+    }
+}
+
+impl<'a, T: 'a, C: RwLockSyscalls> Deref for RwLockWriteGuard<'a, T, C> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<'a, T: 'a, C: RwLockSyscalls> DerefMut for RwLockWriteGuard<'a, T, C> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
@@ -1,6 +1,7 @@
 use zephyr_sys::raw::{k_objects, k_sem, k_timeout_t};
 
 use super::NegErr;
+use crate::error::Result;
 use crate::kobj::*;
 use crate::time::Timeout;
 
@@ -13,9 +14,39 @@ pub use zephyr_sys::raw::k_sem as KSem;
 
 crate::make_static_wrapper!(k_sem, zephyr_sys::raw::k_sem);
 
+#[cfg(static_kobj_init)]
+impl global::k_sem {
+    /// Equivalent of Z_SEM_INITIALIZER, for a static at address `this`. Checks the counts at
+    /// compile time in a static initializer.
+    pub const unsafe fn initializer(
+        this: *const Self,
+        initial_count: libc::c_uint,
+        limit: libc::c_uint,
+    ) -> Self {
+        assert!(
+            limit > 0 && initial_count <= limit,
+            "invalid initial count or limit"
+        );
+        let this = this as *const k_sem;
+        let mut sem: k_sem = core::mem::zeroed();
+        sem.wait_q = wait_q_init(core::ptr::addr_of!((*this).wait_q));
+        sem.count = initial_count;
+        sem.limit = limit;
+        #[cfg(poll)]
+        {
+            sem.poll_events = dlist_init(core::ptr::addr_of!((*this).poll_events));
+        }
+        Self::new(sem)
+    }
+}
+
 /// Raw syscall API
 pub trait SemaphoreSyscalls {
-    unsafe fn k_sem_init(sem: &k_sem, initial_count: libc::c_uint, limit: libc::c_uint);
+    unsafe fn k_sem_init(
+        sem: &k_sem,
+        initial_count: libc::c_uint,
+        limit: libc::c_uint,
+    ) -> libc::c_int;
     fn k_sem_take(sem: &k_sem, timeout: k_timeout_t) -> libc::c_int;
     fn k_sem_give(sem: &k_sem);
     fn k_sem_reset(sem: &k_sem);
@@ -25,12 +56,16 @@ pub trait SemaphoreSyscalls {
 macro_rules! trait_impl {
     ($context:ident, $context_struct:path) => {
         impl SemaphoreSyscalls for $context_struct {
-            unsafe fn k_sem_init(sem: &k_sem, initial_count: libc::c_uint, limit: libc::c_uint) {
+            unsafe fn k_sem_init(
+                sem: &k_sem,
+                initial_count: libc::c_uint,
+                limit: libc::c_uint,
+            ) -> libc::c_int {
                 zephyr_sys::syscalls::$context::k_sem_init(
                     sem as *const _ as *mut _,
                     initial_count,
                     limit,
-                ); // TODO: return the error from here. Ignoring now for Zephyr 2.1 compat
+                )
             }
 
             fn k_sem_take(sem: &k_sem, timeout: k_timeout_t) -> libc::c_int {
@@ -62,46 +97,36 @@ trait_impl!(any, crate::context::Any);
 
 /// Safe API implemented on the sem struct. Converts errors.
 pub trait Semaphore {
-    unsafe fn init<C: SemaphoreSyscalls>(&self, initial_count: u32, limit: u32);
-    /// Take with infinite timeout
-    fn take<C: SemaphoreSyscalls>(&self);
-    /// Take with timeout. Returns true if successful. False if timeout.
-    fn take_timeout<C: SemaphoreSyscalls>(&self, timeout: Timeout) -> bool;
-    /// Take with no timeout. Returns true if successful.
-    fn try_take<C: SemaphoreSyscalls>(&self) -> bool;
+    /// Fails with `InvalidArgument` if `limit` is 0 or less than `initial_count`
+    unsafe fn init<C: SemaphoreSyscalls>(&self, initial_count: u32, limit: u32) -> Result<()>;
+    /// Take with infinite timeout. Fails with `Again` if the semaphore is reset while waiting.
+    fn take<C: SemaphoreSyscalls>(&self) -> Result<()>;
+    /// Take with timeout. Fails with `Again` on timeout, or `Busy` if the timeout is `K_NO_WAIT`.
+    fn take_timeout<C: SemaphoreSyscalls>(&self, timeout: Timeout) -> Result<()>;
+    /// Take with no timeout. Fails with `Busy` if not available.
+    fn try_take<C: SemaphoreSyscalls>(&self) -> Result<()>;
     fn give<C: SemaphoreSyscalls>(&self);
     fn reset<C: SemaphoreSyscalls>(&self);
     fn count<C: SemaphoreSyscalls>(&self) -> u32;
 }
 
 impl Semaphore for k_sem {
-    unsafe fn init<C: SemaphoreSyscalls>(&self, initial_count: u32, limit: u32) {
+    unsafe fn init<C: SemaphoreSyscalls>(&self, initial_count: u32, limit: u32) -> Result<()> {
         C::k_sem_init(&self, initial_count, limit)
+            .neg_err()
+            .map(|_| ())
     }
 
-    fn take<C: SemaphoreSyscalls>(&self) {
-        C::k_sem_take(self, zephyr_sys::raw::K_FOREVER.into())
-            .neg_err()
-            .expect("sem take");
+    fn take<C: SemaphoreSyscalls>(&self) -> Result<()> {
+        self.take_timeout::<C>(crate::K_FOREVER)
     }
 
-    fn take_timeout<C: SemaphoreSyscalls>(&self, timeout: Timeout) -> bool {
-        match C::k_sem_take(self, timeout.0).neg_err() {
-            Ok(_) => Ok(true),
-            Err(zephyr_sys::raw::EBUSY) => Ok(false),
-            Err(zephyr_sys::raw::EAGAIN) => Ok(false),
-            Err(e) => Err(e),
-        }
-        .expect("sem take")
+    fn take_timeout<C: SemaphoreSyscalls>(&self, timeout: Timeout) -> Result<()> {
+        C::k_sem_take(self, timeout.0).neg_err().map(|_| ())
     }
 
-    fn try_take<C: SemaphoreSyscalls>(&self) -> bool {
-        match C::k_sem_take(self, (zephyr_sys::raw::K_NO_WAIT).into()).neg_err() {
-            Ok(_) => Ok(true),
-            Err(zephyr_sys::raw::EBUSY) => Ok(false),
-            Err(e) => Err(e),
-        }
-        .expect("sem take")
+    fn try_take<C: SemaphoreSyscalls>(&self) -> Result<()> {
+        self.take_timeout::<C>(crate::K_NO_WAIT)
     }
 
     fn give<C: SemaphoreSyscalls>(&self) {
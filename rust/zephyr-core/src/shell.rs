@@ -0,0 +1,272 @@
+//! Shell commands implemented in Rust, registered like SHELL_CMD_REGISTER
+//!
+//! ```ignore
+//! use core::fmt::Write;
+//! use zephyr::shell::Shell;
+//!
+//! fn ping(shell: &mut Shell, _args: &[&str]) -> zephyr::error::Result<()> {
+//!     writeln!(shell, "pong").ok();
+//!     Ok(())
+//! }
+//!
+//! fn echo(shell: &mut Shell, args: &[&str]) -> zephyr::error::Result<()> {
+//!     writeln!(shell, "{}", args[1..].join(" ")).ok();
+//!     Ok(())
+//! }
+//!
+//! zephyr::shell_static_subcmd_set_create!(
+//!     SUB_DEMO,
+//!     zephyr::shell_cmd!(ping, None, "Reply with pong", ping),
+//!     zephyr::shell_cmd_arg!(echo, None, "Print the arguments", echo, 2, 255),
+//! );
+//! zephyr::shell_cmd_register!(demo, SUB_DEMO, "Demo commands", None);
+//! ```
+
+use alloc::vec::Vec;
+use core::ffi::CStr;
+use core::fmt;
+use libc::{c_char, c_int};
+
+use crate::error::{Error, Result};
+
+pub use zephyr_sys::raw::shell as RawShell;
+
+/// Function implementing a command. `args[0]` is the command name, like `argv` in C.
+pub type Handler = fn(&mut Shell, &[&str]) -> Result<()>;
+
+/// Function the shell calls for a command, equivalent of `shell_cmd_handler`
+pub type RawHandler = unsafe extern "C" fn(*const RawShell, usize, *mut *mut c_char) -> c_int;
+
+/// Shell instance a command was run from. Text written to it is printed on the shell.
+pub struct Shell<'a>(&'a RawShell);
+
+impl<'a> Shell<'a> {
+    pub fn raw(&self) -> *const RawShell {
+        self.0
+    }
+}
+
+impl<'a> fmt::Write for Shell<'a> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        unsafe { zephyr_sys::raw::rust_shell_write(self.0, s.as_ptr() as *const c_char, s.len()) };
+        Ok(())
+    }
+}
+
+/// Call `handler` for a command run from `shell`. An error is returned to the shell as a negative
+/// errno. Used by the `shell_cmd!` macros.
+#[doc(hidden)]
+pub unsafe fn call(
+    handler: Handler,
+    shell: *const RawShell,
+    argc: usize,
+    argv: *mut *mut c_char,
+) -> c_int {
+    let mut shell = Shell(&*shell);
+    let mut args = Vec::with_capacity(argc);
+    for i in 0..argc {
+        match CStr::from_ptr(*argv.add(i)).to_str() {
+            Ok(arg) => args.push(arg),
+            Err(_) => {
+                fmt::Write::write_str(&mut shell, "argument is not valid UTF-8\n").ok();
+                return -(Error::InvalidArgument.errno() as c_int);
+            }
+        }
+    }
+    match handler(&mut shell, &args) {
+        Ok(()) => 0,
+        Err(e) => -(e.errno() as c_int),
+    }
+}
+
+/// A command or subcommand, equivalent of `struct shell_static_entry`. Created with `shell_cmd!`
+/// or `shell_cmd_arg!`.
+#[repr(transparent)]
+pub struct StaticEntry(zephyr_sys::raw::shell_static_entry);
+
+// Only read by the shell
+unsafe impl Sync for StaticEntry {}
+
+impl StaticEntry {
+    /// Terminates a subcommand set, like SHELL_SUBCMD_SET_END
+    pub const END: Self = StaticEntry(unsafe { core::mem::zeroed() });
+
+    /// Equivalent of SHELL_CMD_ARG. `mandatory` arguments include the command name.
+    ///
+    /// Unsafe because `syntax` and `help` must be nul-terminated.
+    pub const unsafe fn new(
+        syntax: &'static str,
+        help: &'static str,
+        subcmd: Option<&'static CmdEntry>,
+        handler: Option<RawHandler>,
+        mandatory: u8,
+        optional: u8,
+    ) -> Self {
+        let mut entry: zephyr_sys::raw::shell_static_entry = core::mem::zeroed();
+        entry.syntax = syntax.as_ptr() as *const _;
+        entry.help = help.as_ptr() as *const _;
+        entry.subcmd = match subcmd {
+            Some(subcmd) => &subcmd.0,
+            None => core::ptr::null(),
+        };
+        entry.handler = handler;
+        entry.args.mandatory = mandatory;
+        entry.args.optional = optional;
+        StaticEntry(entry)
+    }
+}
+
+/// A set of subcommands, equivalent of `struct shell_cmd_entry`. Created with
+/// `shell_static_subcmd_set_create!`.
+#[repr(transparent)]
+pub struct CmdEntry(zephyr_sys::raw::shell_cmd_entry);
+
+// Only read by the shell
+unsafe impl Sync for CmdEntry {}
+
+impl CmdEntry {
+    /// Unsafe because `entries` must end with `StaticEntry::END`
+    pub const unsafe fn new(entries: &'static [StaticEntry]) -> Self {
+        Self::from_ptr(entries.as_ptr())
+    }
+
+    /// Equivalent of a root command registered with SHELL_CMD_REGISTER
+    pub const fn root(entry: &'static StaticEntry) -> Self {
+        Self::from_ptr(entry)
+    }
+
+    const fn from_ptr(entry: *const StaticEntry) -> Self {
+        let mut cmd: zephyr_sys::raw::shell_cmd_entry = unsafe { core::mem::zeroed() };
+        // Whether an entry is dynamic is known from its section since the shell switched to
+        // iterable sections
+        #[cfg(zephyr350)]
+        {
+            cmd.entry = entry as *const _;
+        }
+        #[cfg(not(zephyr350))]
+        {
+            cmd.is_dynamic = false;
+            cmd.u.entry = entry as *const _;
+        }
+        CmdEntry(cmd)
+    }
+}
+
+/// `shell_cmd_arg!(syntax, subcmd, "help", handler, mandatory, optional)` is a command entry for
+/// `shell_static_subcmd_set_create!`, like SHELL_CMD_ARG. `subcmd` is a subcommand set or `None`.
+/// `handler` is a `Handler` path or `None`. `mandatory` counts the command name itself.
+#[macro_export]
+macro_rules! shell_cmd_arg {
+    ($syntax:ident, $subcmd:ident, $help:literal, $($handler:ident)::+, $mandatory:expr,
+     $optional:expr) => {
+        unsafe {
+            $crate::shell::StaticEntry::new(
+                concat!(stringify!($syntax), "\0"),
+                concat!($help, "\0"),
+                $crate::shell_subcmd!($subcmd),
+                $crate::shell_handler!($($handler)::+),
+                $mandatory,
+                $optional,
+            )
+        }
+    };
+}
+
+/// `shell_cmd!(syntax, subcmd, "help", handler)` is `shell_cmd_arg!` without argument checks,
+/// like SHELL_CMD
+#[macro_export]
+macro_rules! shell_cmd {
+    ($syntax:ident, $subcmd:ident, $help:literal, $($handler:ident)::+) => {
+        $crate::shell_cmd_arg!($syntax, $subcmd, $help, $($handler)::+, 0, 0)
+    };
+}
+
+/// `shell_static_subcmd_set_create!(NAME, entries...)` defines a set of subcommands to pass as the
+/// `subcmd` of a command, like SHELL_STATIC_SUBCMD_SET_CREATE
+#[macro_export]
+macro_rules! shell_static_subcmd_set_create {
+    ($name:ident, $($entry:expr),* $(,)?) => {
+        static $name: $crate::shell::CmdEntry = unsafe {
+            $crate::shell::CmdEntry::new(&[$($entry,)* $crate::shell::StaticEntry::END])
+        };
+    };
+}
+
+/// `shell_cmd_arg_register!(syntax, subcmd, "help", handler, mandatory, optional)` registers a
+/// root command, like SHELL_CMD_ARG_REGISTER. Arguments are as for `shell_cmd_arg!`.
+#[macro_export]
+macro_rules! shell_cmd_arg_register {
+    ($syntax:ident, $($args:tt)*) => {
+        const _: () = {
+            static ENTRY: $crate::shell::StaticEntry = $crate::shell_cmd_arg!($syntax, $($args)*);
+
+            #[used]
+            #[link_section = $crate::shell_root_section!($syntax)]
+            static CMD: $crate::shell::CmdEntry = $crate::shell::CmdEntry::root(&ENTRY);
+        };
+    };
+}
+
+/// `shell_cmd_register!(syntax, subcmd, "help", handler)` registers a root command without
+/// argument checks, like SHELL_CMD_REGISTER
+#[macro_export]
+macro_rules! shell_cmd_register {
+    ($syntax:ident, $subcmd:ident, $help:literal, $($handler:ident)::+) => {
+        $crate::shell_cmd_arg_register!($syntax, $subcmd, $help, $($handler)::+, 0, 0);
+    };
+}
+
+/// Used by the shell macros
+#[doc(hidden)]
+#[macro_export]
+macro_rules! shell_subcmd {
+    (None) => {
+        None
+    };
+    ($subcmd:ident) => {
+        Some(&$subcmd)
+    };
+}
+
+/// Used by the shell macros. A C handler calling a Rust `Handler`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! shell_handler {
+    (None) => {
+        None
+    };
+    ($($handler:ident)::+) => {{
+        unsafe extern "C" fn handler(
+            shell: *const $crate::shell::RawShell,
+            argc: usize,
+            argv: *mut *mut core::ffi::c_char,
+        ) -> core::ffi::c_int {
+            $crate::shell::call($($handler)::+, shell, argc, argv)
+        }
+        Some(handler as $crate::shell::RawHandler)
+    }};
+}
+
+/// Used by `shell_cmd_arg_register!`. Section of the root command table, named after the
+/// `shell_cmd_<syntax>_` struct SHELL_CMD_ARG_REGISTER defines so the linker sorts it the same way.
+#[cfg(zephyr350)]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! shell_root_section {
+    ($syntax:ident) => {
+        concat!(
+            "._shell_root_cmds.static.shell_cmd_",
+            stringify!($syntax),
+            "_"
+        )
+    };
+}
+/// See above
+#[cfg(not(zephyr350))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! shell_root_section {
+    ($syntax:ident) => {
+        concat!(".shell_root_cmd_", stringify!($syntax))
+    };
+}
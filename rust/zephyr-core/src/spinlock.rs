@@ -0,0 +1,89 @@
+//! Spinlock-protected data that can be shared with ISRs
+//!
+//! k_spin_lock is an inline function in Zephyr, so it is called through a C wrapper. It locks
+//! interrupts, so like `IrqLock` this is kernel-only.
+
+use core::cell::UnsafeCell;
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
+
+use zephyr_sys::raw::{k_spinlock, k_spinlock_key_t};
+
+/// Raw API. Only implemented for the kernel context.
+pub trait SpinlockSyscalls {
+    unsafe fn k_spin_lock(lock: *mut k_spinlock) -> k_spinlock_key_t;
+}
+
+impl SpinlockSyscalls for crate::context::Kernel {
+    unsafe fn k_spin_lock(lock: *mut k_spinlock) -> k_spinlock_key_t {
+        zephyr_sys::raw::rust_k_spin_lock(lock)
+    }
+}
+
+/// Mutual exclusion over a k_spinlock
+///
+/// Locking disables interrupts on the current CPU and, on SMP, spins until no other CPU holds
+/// the lock. Unlike `Mutex`, it can be used from ISRs and needs no kernel object, so it can be
+/// declared as a plain static. It is not recursive: locking it again on the same CPU deadlocks.
+/// Keep critical sections short and never block while holding the lock.
+pub struct SpinMutex<T> {
+    lock: UnsafeCell<k_spinlock>,
+    data: UnsafeCell<T>,
+}
+
+unsafe impl<T: Send> Send for SpinMutex<T> {}
+unsafe impl<T: Send> Sync for SpinMutex<T> {}
+
+impl<T> SpinMutex<T> {
+    pub const fn new(data: T) -> Self {
+        SpinMutex {
+            lock: UnsafeCell::new(unsafe { core::mem::zeroed() }),
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    pub fn lock<C: SpinlockSyscalls>(&self) -> SpinMutexGuard<T> {
+        let key = unsafe { C::k_spin_lock(self.lock.get()) };
+        SpinMutexGuard {
+            mutex: self,
+            key,
+            _not_send: PhantomData,
+        }
+    }
+
+    /// Access without locking, which is safe when the mutex is borrowed mutably
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+pub struct SpinMutexGuard<'a, T> {
+    mutex: &'a SpinMutex<T>,
+    key: k_spinlock_key_t,
+    // Must be unlocked on the CPU that locked it
+    _not_send: PhantomData<*const ()>,
+}
+
+impl<'a, T> Deref for SpinMutexGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.mutex.data.get() }
+    }
+}
+
+impl<'a, T> DerefMut for SpinMutexGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.mutex.data.get() }
+    }
+}
+
+impl<'a, T> Drop for SpinMutexGuard<'a, T> {
+    fn drop(&mut self) {
+        unsafe { zephyr_sys::raw::rust_k_spin_unlock(self.mutex.lock.get(), self.key) }
+    }
+}
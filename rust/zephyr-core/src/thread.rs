@@ -1,6 +1,26 @@
+use core::ffi::CStr;
 use core::ptr::NonNull;
 
+use libc::c_int;
+
+use super::NegErr;
+use crate::error::{Error, Result};
 use crate::kobj::KObj;
+use crate::time::Timeout;
+
+pub use zephyr_sys::raw::k_thread_stack_t as KThreadStack;
+
+unsafe impl KObj for zephyr_sys::raw::k_thread {
+    const OTYPE: zephyr_sys::raw::k_objects = zephyr_sys::raw::k_objects_K_OBJ_THREAD;
+}
+
+crate::make_static_wrapper!(k_thread, zephyr_sys::raw::k_thread);
+
+impl global::k_thread {
+    pub fn tid(&self) -> ThreadId {
+        ThreadId(NonNull::from(self.kobj()))
+    }
+}
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct ThreadId(NonNull<zephyr_sys::raw::k_thread>);
@@ -21,11 +41,73 @@ impl ThreadId {
     pub fn k_object_access_grant<C: ThreadSyscalls, K: KObj>(&self, kobj: &K) {
         C::k_object_access_grant(kobj, *self)
     }
+
+    pub fn k_thread_priority_get<C: ThreadSyscalls>(&self) -> c_int {
+        C::k_thread_priority_get(*self)
+    }
+
+    pub fn k_thread_priority_set<C: ThreadSyscalls>(&self, priority: c_int) {
+        C::k_thread_priority_set(*self, priority)
+    }
+
+    /// Set the thread's name. The kernel keeps a copy. Fails with `NotImplemented` if
+    /// CONFIG_THREAD_NAME is disabled, or `InvalidArgument` if the name is too long.
+    pub fn k_thread_name_set<C: ThreadSyscalls>(&self, name: &CStr) -> Result<()> {
+        C::k_thread_name_set(*self, name).neg_err().map(|_| ())
+    }
+
+    /// Copy the thread's name into `buf`, returning the part holding the name. Fails with
+    /// `NotImplemented` if CONFIG_THREAD_NAME is disabled, `NoSpace` if the name does not fit, or
+    /// `InvalidArgument` if it is not UTF-8.
+    pub fn k_thread_name_copy<'b, C: ThreadSyscalls>(&self, buf: &'b mut [u8]) -> Result<&'b str> {
+        C::k_thread_name_copy(*self, buf).neg_err()?;
+        let name = CStr::from_bytes_until_nul(buf).map_err(|_| Error::NoSpace)?;
+        name.to_str().map_err(|_| Error::InvalidArgument)
+    }
+
+    /// Abort the thread. Does not return if the thread is the caller.
+    pub fn k_thread_abort<C: ThreadSyscalls>(&self) {
+        C::k_thread_abort(*self)
+    }
+
+    /// Wait for the thread to exit. Fails with `Busy` if it is still running and the timeout is
+    /// `K_NO_WAIT`, `Again` on timeout, or `Deadlock` if the thread is the caller or is itself
+    /// joining the caller.
+    pub fn k_thread_join<C: ThreadSyscalls>(&self, timeout: Timeout) -> Result<()> {
+        C::k_thread_join(*self, timeout.0).neg_err().map(|_| ())
+    }
+
+    /// Resume a suspended thread
+    pub fn k_thread_resume<C: ThreadSyscalls>(&self) {
+        C::k_thread_resume(*self)
+    }
+
+    /// Start a thread that was created with a `K_FOREVER` delay
+    pub fn k_thread_start<C: ThreadSyscalls>(&self) {
+        C::k_thread_start(*self)
+    }
+
+    /// Set the deadline, in cycles from now, used to order threads of equal priority. Only with
+    /// CONFIG_SCHED_DEADLINE.
+    #[cfg(sched_deadline)]
+    pub fn k_thread_deadline_set<C: ThreadSyscalls>(&self, deadline: c_int) {
+        C::k_thread_deadline_set(*self, deadline)
+    }
 }
 
 pub trait ThreadSyscalls {
     fn k_thread_suspend(thread: ThreadId);
     fn k_wakeup(thread: ThreadId);
+    fn k_thread_priority_get(thread: ThreadId) -> c_int;
+    fn k_thread_priority_set(thread: ThreadId, priority: c_int);
+    fn k_thread_name_set(thread: ThreadId, name: &CStr) -> c_int;
+    fn k_thread_name_copy(thread: ThreadId, buf: &mut [u8]) -> c_int;
+    fn k_thread_abort(thread: ThreadId);
+    fn k_thread_join(thread: ThreadId, timeout: zephyr_sys::raw::k_timeout_t) -> c_int;
+    fn k_thread_resume(thread: ThreadId);
+    fn k_thread_start(thread: ThreadId);
+    #[cfg(sched_deadline)]
+    fn k_thread_deadline_set(thread: ThreadId, deadline: c_int);
     fn k_current_get() -> crate::thread::ThreadId;
     fn k_object_access_grant<K: KObj>(kobj: &K, thread: ThreadId);
 }
@@ -41,6 +123,55 @@ macro_rules! trait_impl {
                 unsafe { zephyr_sys::syscalls::$context::k_wakeup(thread.tid()) }
             }
 
+            fn k_thread_priority_get(thread: ThreadId) -> c_int {
+                unsafe { zephyr_sys::syscalls::$context::k_thread_priority_get(thread.tid()) }
+            }
+
+            fn k_thread_priority_set(thread: ThreadId, priority: c_int) {
+                unsafe {
+                    zephyr_sys::syscalls::$context::k_thread_priority_set(thread.tid(), priority)
+                }
+            }
+
+            fn k_thread_name_set(thread: ThreadId, name: &CStr) -> c_int {
+                unsafe {
+                    zephyr_sys::syscalls::$context::k_thread_name_set(thread.tid(), name.as_ptr())
+                }
+            }
+
+            fn k_thread_name_copy(thread: ThreadId, buf: &mut [u8]) -> c_int {
+                unsafe {
+                    zephyr_sys::syscalls::$context::k_thread_name_copy(
+                        thread.tid(),
+                        buf.as_mut_ptr() as *mut libc::c_char,
+                        buf.len(),
+                    )
+                }
+            }
+
+            fn k_thread_abort(thread: ThreadId) {
+                unsafe { zephyr_sys::syscalls::$context::k_thread_abort(thread.tid()) }
+            }
+
+            fn k_thread_join(thread: ThreadId, timeout: zephyr_sys::raw::k_timeout_t) -> c_int {
+                unsafe { zephyr_sys::syscalls::$context::k_thread_join(thread.tid(), timeout) }
+            }
+
+            fn k_thread_resume(thread: ThreadId) {
+                unsafe { zephyr_sys::syscalls::$context::k_thread_resume(thread.tid()) }
+            }
+
+            fn k_thread_start(thread: ThreadId) {
+                unsafe { zephyr_sys::syscalls::$context::k_thread_start(thread.tid()) }
+            }
+
+            #[cfg(sched_deadline)]
+            fn k_thread_deadline_set(thread: ThreadId, deadline: c_int) {
+                unsafe {
+                    zephyr_sys::syscalls::$context::k_thread_deadline_set(thread.tid(), deadline)
+                }
+            }
+
             /* If less than 2.7, always use the k_current_get syscall */
             #[cfg(not(zephyr270))]
             fn k_current_get() -> crate::thread::ThreadId {
@@ -96,3 +227,427 @@ macro_rules! trait_impl {
 trait_impl!(kernel, crate::context::Kernel);
 trait_impl!(user, crate::context::User);
 trait_impl!(any, crate::context::Any);
+
+/// Bytes of a kernel stack object the kernel reserves for itself, such as a guard area
+#[cfg(zephyr250)]
+pub const KERNEL_STACK_RESERVED: usize = zephyr_sys::raw::RUST_KERNEL_STACK_RESERVED;
+
+/// Stack for a thread that only runs in kernel mode, like K_KERNEL_STACK_DEFINE
+///
+/// Define with `k_kernel_stack_define!` so it is not placed in the Rust user partition. `N` is the
+/// size of the whole stack object, including `KERNEL_STACK_RESERVED`, which the macro adds to the
+/// usable size like K_KERNEL_STACK_DEFINE does.
+///
+/// Aligned to 64 bytes, or a page on x86 when the kernel puts guard pages around stacks.
+#[cfg(zephyr250)]
+#[cfg_attr(not(kernel_stack_page_align), repr(C, align(64)))]
+#[cfg_attr(kernel_stack_page_align, repr(C, align(4096)))]
+pub struct KernelStack<const N: usize>(core::cell::UnsafeCell<[u8; N]>);
+
+#[cfg(zephyr250)]
+unsafe impl<const N: usize> Sync for KernelStack<N> {}
+
+#[cfg(zephyr250)]
+impl<const N: usize> KernelStack<N> {
+    const VALID: () = {
+        assert!(
+            zephyr_sys::raw::RUST_KERNEL_STACK_OBJ_ALIGN <= core::mem::align_of::<Self>(),
+            "kernel stack alignment is larger than KernelStack provides"
+        );
+        assert!(
+            N > zephyr_sys::raw::RUST_KERNEL_STACK_RESERVED,
+            "kernel stack is smaller than its reserved area"
+        );
+    };
+
+    pub const fn new() -> Self {
+        KernelStack(core::cell::UnsafeCell::new([0; N]))
+    }
+
+    pub const fn as_ptr(&self) -> *mut zephyr_sys::raw::k_thread_stack_t {
+        self.0.get() as *mut _
+    }
+
+    /// Size to pass to the kernel when creating a thread, excluding the reserved area
+    pub const fn size(&self) -> usize {
+        let _ = Self::VALID;
+        N - zephyr_sys::raw::RUST_KERNEL_STACK_RESERVED
+    }
+}
+
+/// Entry in the kernel's table of threads to create at boot, like K_THREAD_DEFINE makes. Defined
+/// with `#[zephyr::thread]`.
+#[cfg(zephyr250)]
+#[repr(transparent)]
+pub struct StaticThreadData(zephyr_sys::raw::_static_thread_data);
+
+// Only read by the kernel, at boot
+#[cfg(zephyr250)]
+unsafe impl Sync for StaticThreadData {}
+
+#[cfg(zephyr250)]
+impl StaticThreadData {
+    /// Equivalent of Z_THREAD_INITIALIZER. A `delay_ms` of -1 leaves the thread for
+    /// `k_thread_start`, like SYS_FOREVER_MS.
+    ///
+    /// Unsafe because the thread object and stack must not be used for anything else, and `name`
+    /// must be nul-terminated.
+    pub const unsafe fn new(
+        thread: &'static global::k_thread,
+        stack: *mut KThreadStack,
+        stack_size: usize,
+        entry: extern "C" fn(*mut libc::c_void, *mut libc::c_void, *mut libc::c_void),
+        priority: c_int,
+        options: u32,
+        delay_ms: i32,
+        name: &'static str,
+    ) -> Self {
+        let mut data: zephyr_sys::raw::_static_thread_data = core::mem::zeroed();
+        data.init_thread = thread.kobj() as *const _ as *mut _;
+        data.init_stack = stack;
+        data.init_stack_size = stack_size as _;
+        data.init_entry = Some(entry);
+        data.init_prio = priority;
+        data.init_options = options;
+        data.init_name = name.as_ptr() as *const _;
+        #[cfg(not(zephyr300))]
+        {
+            data.init_delay = delay_ms;
+        }
+        #[cfg(all(zephyr300, timer_reads_frequency_at_runtime))]
+        {
+            data.init_delay_ms = delay_ms;
+        }
+        #[cfg(all(zephyr300, not(timer_reads_frequency_at_runtime)))]
+        {
+            // SYS_TIMEOUT_MS_INIT
+            data.init_delay = if delay_ms == -1 {
+                zephyr_sys::raw::K_FOREVER
+            } else {
+                let ms = if delay_ms < 0 { 0 } else { delay_ms as u64 };
+                let ticks = time_convert::z_tmcvt(
+                    ms,
+                    1_000,
+                    zephyr_sys::raw::Z_HZ_ticks,
+                    true,
+                    true,
+                    false,
+                );
+                zephyr_sys::raw::k_timeout_t { ticks: ticks as _ }
+            };
+        }
+        StaticThreadData(data)
+    }
+}
+
+/// Stop a user thread from being defined on a kernel-only stack. Used by `#[zephyr::thread]`.
+#[doc(hidden)]
+pub const fn assert_user_stack(user_capable: bool) {
+    #[cfg(usermode)]
+    assert!(
+        user_capable,
+        "user threads need a stack defined in C with K_THREAD_STACK_DEFINE"
+    );
+    let _ = user_capable;
+}
+
+/// Get a pointer to a thread stack defined in C with K_THREAD_STACK_DEFINE
+#[macro_export]
+macro_rules! static_thread_stack {
+    ($stack:ident) => {{
+        extern "C" {
+            static $stack: $crate::thread::KThreadStack;
+        }
+
+        unsafe { core::ptr::addr_of!($stack) as *mut $crate::thread::KThreadStack }
+    }};
+}
+
+/// Thread options for `Builder::options`, matching the K_* thread option bits
+pub mod options {
+    /// Thread is essential to the system. Its exit or abort is a fatal error.
+    pub const K_ESSENTIAL: u32 = 1 << 0;
+    /// Thread uses floating point registers
+    pub const K_FP_REGS: u32 = 1 << 1;
+    /// Thread starts in user mode
+    pub const K_USER: u32 = 1 << 2;
+    /// Thread inherits the creating thread's kernel object permissions
+    pub const K_INHERIT_PERMS: u32 = 1 << 3;
+}
+
+#[cfg(zephyr250)]
+enum StackSource {
+    /// Owned by someone else. Never freed.
+    Static {
+        stack: *mut zephyr_sys::raw::k_thread_stack_t,
+        size: usize,
+        user: bool,
+    },
+    /// Allocated from the Rust heap when the thread is spawned
+    Heap(usize),
+}
+
+#[cfg(zephyr250)]
+struct HeapStack {
+    ptr: *mut u8,
+    layout: core::alloc::Layout,
+}
+
+#[cfg(zephyr250)]
+impl HeapStack {
+    /// Allocate a kernel stack with at least `size` usable bytes. Returns the stack and the size
+    /// to pass to the kernel.
+    fn new(size: usize) -> (Self, usize) {
+        use zephyr_sys::raw::{RUST_KERNEL_STACK_OBJ_ALIGN, RUST_KERNEL_STACK_RESERVED};
+
+        let layout = core::alloc::Layout::from_size_align(
+            size + RUST_KERNEL_STACK_RESERVED,
+            RUST_KERNEL_STACK_OBJ_ALIGN,
+        )
+        .expect("thread stack layout")
+        .pad_to_align();
+        let ptr = unsafe { alloc::alloc::alloc(layout) };
+        if ptr.is_null() {
+            alloc::alloc::handle_alloc_error(layout);
+        }
+        (HeapStack { ptr, layout }, layout.size() - RUST_KERNEL_STACK_RESERVED)
+    }
+}
+
+#[cfg(zephyr250)]
+impl Drop for HeapStack {
+    fn drop(&mut self) {
+        unsafe { alloc::alloc::dealloc(self.ptr, self.layout) }
+    }
+}
+
+/// Where a spawned thread leaves its result
+#[cfg(zephyr250)]
+struct Packet<T>(core::cell::UnsafeCell<Option<T>>);
+
+// Only written by the thread before it exits and only read after joining it
+#[cfg(zephyr250)]
+unsafe impl<T: Send> Sync for Packet<T> {}
+
+#[cfg(zephyr250)]
+extern "C" fn spawn_entry<F, T>(
+    f: *mut libc::c_void,
+    packet: *mut libc::c_void,
+    _: *mut libc::c_void,
+) where
+    F: FnOnce() -> T,
+{
+    unsafe {
+        let f = alloc::boxed::Box::from_raw(f as *mut F);
+        let packet = alloc::sync::Arc::from_raw(packet as *const Packet<T>);
+        *packet.0.get() = Some(f());
+    }
+}
+
+/// Create threads running Rust closures, like `std::thread::Builder`
+///
+/// The stack comes either from the heap, sized with `stack_size`, or from a static stack. Heap
+/// and `KernelStack` stacks are kernel-only, so `K_USER` threads need a user-capable stack
+/// defined in C with K_THREAD_STACK_DEFINE and passed with `raw_stack`. A user thread also needs
+/// access to the Rust heap partition, where its closure and result live.
+///
+/// Spawning is kernel-only because the k_thread struct is allocated on the Rust heap, which is
+/// not a kernel object user mode could pass to k_thread_create.
+///
+/// ```ignore
+/// let handle = Builder::new()
+///     .stack_size(1024)
+///     .priority(5)
+///     .name("worker")
+///     .spawn(|| 6 * 7);
+/// assert_eq!(handle.join(), Ok(42));
+/// ```
+#[cfg(zephyr250)]
+pub struct Builder {
+    stack: Option<StackSource>,
+    priority: Option<libc::c_int>,
+    options: u32,
+    name: Option<alloc::ffi::CString>,
+    delay: Timeout,
+}
+
+#[cfg(zephyr250)]
+impl Builder {
+    pub fn new() -> Self {
+        Builder {
+            stack: None,
+            priority: None,
+            options: 0,
+            name: None,
+            delay: crate::K_NO_WAIT,
+        }
+    }
+
+    /// Allocate a stack with at least `size` usable bytes from the heap. It is freed when the
+    /// thread is joined.
+    pub fn stack_size(mut self, size: usize) -> Self {
+        self.stack = Some(StackSource::Heap(size));
+        self
+    }
+
+    /// Run on a static kernel stack, e.g. one defined with `k_kernel_stack_define!`
+    ///
+    /// Unsafe because the stack must not be in use by any other thread until this one exits.
+    pub unsafe fn stack<const N: usize>(mut self, stack: &'static KernelStack<N>) -> Self {
+        self.stack = Some(StackSource::Static {
+            stack: stack.as_ptr(),
+            size: stack.size(),
+            user: false,
+        });
+        self
+    }
+
+    /// Run on a stack defined in C, e.g. with K_THREAD_STACK_DEFINE, which can be used for user
+    /// threads. `size` is what K_THREAD_STACK_SIZEOF gives for it.
+    ///
+    /// Unsafe because the stack must be valid and not be in use by any other thread until this
+    /// one exits.
+    pub unsafe fn raw_stack(
+        mut self,
+        stack: *mut zephyr_sys::raw::k_thread_stack_t,
+        size: usize,
+    ) -> Self {
+        self.stack = Some(StackSource::Static {
+            stack,
+            size,
+            user: true,
+        });
+        self
+    }
+
+    /// Thread priority. Defaults to the priority of the spawning thread.
+    pub fn priority(mut self, priority: libc::c_int) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Thread options, from the `options` module
+    pub fn options(mut self, options: u32) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Thread name. Only kept if CONFIG_THREAD_NAME is enabled.
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(alloc::ffi::CString::new(name).expect("thread name contains a nul"));
+        self
+    }
+
+    /// Delay before the thread starts. `K_FOREVER` leaves it for a later k_thread_start.
+    pub fn delay(mut self, delay: Timeout) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Spawn the thread, returning a handle to join it
+    ///
+    /// Panics if no stack was given, or if `K_USER` is requested with a kernel-only stack.
+    pub fn spawn<F, T>(self, f: F) -> JoinHandle<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        use alloc::boxed::Box;
+        use alloc::sync::Arc;
+        use zephyr_sys::syscalls::kernel;
+
+        let (heap_stack, stack, size, user_capable) =
+            match self.stack.expect("thread spawned without a stack") {
+                StackSource::Static { stack, size, user } => (None, stack, size, user),
+                StackSource::Heap(size) => {
+                    let (heap_stack, size) = HeapStack::new(size);
+                    let stack = heap_stack.ptr as *mut _;
+                    (Some(heap_stack), stack, size, false)
+                }
+            };
+        if self.options & options::K_USER != 0 && !user_capable {
+            panic!("user threads need a stack defined in C");
+        }
+
+        let priority = self.priority.unwrap_or_else(|| {
+            crate::context::Kernel::k_current_get()
+                .k_thread_priority_get::<crate::context::Kernel>()
+        });
+        let packet = Arc::new(Packet(core::cell::UnsafeCell::new(None)));
+        let thread: Box<zephyr_sys::raw::k_thread> = Box::new(unsafe { core::mem::zeroed() });
+        let thread = Box::into_raw(thread);
+
+        let tid = unsafe {
+            kernel::k_thread_create(
+                thread,
+                stack,
+                size,
+                Some(spawn_entry::<F, T>),
+                Box::into_raw(Box::new(f)) as *mut libc::c_void,
+                Arc::into_raw(packet.clone()) as *mut libc::c_void,
+                core::ptr::null_mut(),
+                priority,
+                self.options,
+                self.delay.0,
+            )
+        };
+        if let Some(name) = &self.name {
+            // Fails only without CONFIG_THREAD_NAME
+            unsafe { kernel::k_thread_name_set(tid, name.as_ptr()) };
+        }
+
+        JoinHandle {
+            thread: Some(unsafe { Box::from_raw(thread) }),
+            stack: heap_stack,
+            packet,
+        }
+    }
+}
+
+/// Owned permission to join a spawned thread
+///
+/// Dropping the handle detaches the thread. Its k_thread struct and heap stack are then leaked,
+/// since the kernel may still be using them.
+#[cfg(zephyr250)]
+pub struct JoinHandle<T> {
+    thread: Option<alloc::boxed::Box<zephyr_sys::raw::k_thread>>,
+    stack: Option<HeapStack>,
+    packet: alloc::sync::Arc<Packet<T>>,
+}
+
+#[cfg(zephyr250)]
+unsafe impl<T: Send> Send for JoinHandle<T> {}
+#[cfg(zephyr250)]
+unsafe impl<T: Send> Sync for JoinHandle<T> {}
+
+#[cfg(zephyr250)]
+impl<T> JoinHandle<T> {
+    pub fn thread(&self) -> ThreadId {
+        let thread = self.thread.as_ref().unwrap();
+        ThreadId(NonNull::from(&**thread))
+    }
+
+    /// Wait for the thread to exit and return its closure's result
+    ///
+    /// Returns `Error::Canceled` if the thread was aborted before the closure returned. If the
+    /// join itself fails, the thread is left running and its stack is leaked as on drop.
+    pub fn join(mut self) -> Result<T> {
+        self.thread()
+            .k_thread_join::<crate::context::Kernel>(crate::K_FOREVER)?;
+        let thread = self.thread.take().unwrap();
+        // The thread has exited, so the kernel is done with its struct and stack
+        drop(thread);
+        drop(self.stack.take());
+        unsafe { (*self.packet.0.get()).take() }.ok_or(Error::Canceled)
+    }
+}
+
+#[cfg(zephyr250)]
+impl<T> Drop for JoinHandle<T> {
+    fn drop(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            alloc::boxed::Box::leak(thread);
+            core::mem::forget(self.stack.take());
+        }
+    }
+}
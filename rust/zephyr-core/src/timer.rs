@@ -0,0 +1,137 @@
+use libc::c_void;
+use zephyr_sys::raw::{k_objects, k_timeout_t, k_timer};
+
+use crate::kobj::*;
+use crate::poll::{KPollSignal, Signal};
+use crate::time::Timeout;
+
+// Declare the Zephyr struct to be a kernel object
+unsafe impl KObj for k_timer {
+    const OTYPE: k_objects = zephyr_sys::raw::k_objects_K_OBJ_TIMER;
+}
+
+pub use zephyr_sys::raw::k_timer as KTimer;
+
+crate::make_static_wrapper!(k_timer, zephyr_sys::raw::k_timer);
+
+/// Raw syscall API
+pub trait TimerSyscalls {
+    fn k_timer_start(timer: &k_timer, duration: k_timeout_t, period: k_timeout_t);
+    fn k_timer_stop(timer: &k_timer);
+    fn k_timer_status_get(timer: &k_timer) -> u32;
+    fn k_timer_status_sync(timer: &k_timer) -> u32;
+}
+
+macro_rules! trait_impl {
+    ($context:ident, $context_struct:path) => {
+        impl TimerSyscalls for $context_struct {
+            fn k_timer_start(timer: &k_timer, duration: k_timeout_t, period: k_timeout_t) {
+                unsafe {
+                    zephyr_sys::syscalls::$context::k_timer_start(
+                        timer as *const _ as *mut _,
+                        duration,
+                        period,
+                    )
+                }
+            }
+
+            fn k_timer_stop(timer: &k_timer) {
+                unsafe { zephyr_sys::syscalls::$context::k_timer_stop(timer as *const _ as *mut _) }
+            }
+
+            fn k_timer_status_get(timer: &k_timer) -> u32 {
+                unsafe {
+                    zephyr_sys::syscalls::$context::k_timer_status_get(timer as *const _ as *mut _)
+                }
+            }
+
+            fn k_timer_status_sync(timer: &k_timer) -> u32 {
+                unsafe {
+                    zephyr_sys::syscalls::$context::k_timer_status_sync(timer as *const _ as *mut _)
+                }
+            }
+        }
+    };
+}
+
+trait_impl!(kernel, crate::context::Kernel);
+trait_impl!(user, crate::context::User);
+trait_impl!(any, crate::context::Any);
+
+extern "C" fn closure_expiry<F: Fn() + Sync>(timer: *mut k_timer) {
+    unsafe {
+        let f = &*(zephyr_sys::syscalls::kernel::k_timer_user_data_get(timer) as *const F);
+        f();
+    }
+}
+
+extern "C" fn signal_expiry(timer: *mut k_timer) {
+    unsafe {
+        let signal =
+            &*(zephyr_sys::syscalls::kernel::k_timer_user_data_get(timer) as *const KPollSignal);
+        signal.raise::<crate::context::Kernel>(0);
+    }
+}
+
+/// Safe API implemented on the timer struct
+///
+/// The init functions are kernel-only and unsafe because the timer must not be running. The
+/// expiry function, if any, runs in interrupt context and the timer's user data is used to find
+/// it, so it must not be changed afterwards.
+pub trait Timer {
+    /// Initialize with no expiry function. Use `status_get` or `status_sync` to observe expiry.
+    unsafe fn init(&self);
+    /// Initialize to call `expiry` from the timer interrupt each time the timer expires
+    unsafe fn init_with_expiry<F: Fn() + Sync>(&self, expiry: &'static F);
+    /// Initialize to raise `signal` each time the timer expires, so the expiry can be waited on
+    /// with k_poll
+    unsafe fn init_with_signal(&self, signal: &'static KPollSignal);
+    /// Start the timer. It first expires after `duration`, then every `period`. A period of
+    /// `K_NO_WAIT` makes a one-shot timer. Restarting a running timer resets its status.
+    fn start<C: TimerSyscalls>(&self, duration: Timeout, period: Timeout);
+    fn stop<C: TimerSyscalls>(&self);
+    /// Number of times the timer has expired since the status was last read. Resets the status.
+    fn status_get<C: TimerSyscalls>(&self) -> u32;
+    /// Block until the timer expires or is stopped, unless it has already expired since the
+    /// status was last read. Returns the number of expiries and resets the status. Returns 0 if
+    /// the timer was stopped or is not running.
+    fn status_sync<C: TimerSyscalls>(&self) -> u32;
+}
+
+impl Timer for k_timer {
+    unsafe fn init(&self) {
+        zephyr_sys::raw::k_timer_init(self as *const _ as *mut _, None, None);
+    }
+
+    unsafe fn init_with_expiry<F: Fn() + Sync>(&self, expiry: &'static F) {
+        zephyr_sys::raw::k_timer_init(self as *const _ as *mut _, Some(closure_expiry::<F>), None);
+        zephyr_sys::syscalls::kernel::k_timer_user_data_set(
+            self as *const _ as *mut _,
+            expiry as *const F as *mut c_void,
+        );
+    }
+
+    unsafe fn init_with_signal(&self, signal: &'static KPollSignal) {
+        zephyr_sys::raw::k_timer_init(self as *const _ as *mut _, Some(signal_expiry), None);
+        zephyr_sys::syscalls::kernel::k_timer_user_data_set(
+            self as *const _ as *mut _,
+            signal as *const KPollSignal as *mut c_void,
+        );
+    }
+
+    fn start<C: TimerSyscalls>(&self, duration: Timeout, period: Timeout) {
+        C::k_timer_start(self, duration.0, period.0)
+    }
+
+    fn stop<C: TimerSyscalls>(&self) {
+        C::k_timer_stop(self)
+    }
+
+    fn status_get<C: TimerSyscalls>(&self) -> u32 {
+        C::k_timer_status_get(self)
+    }
+
+    fn status_sync<C: TimerSyscalls>(&self) -> u32 {
+        C::k_timer_status_sync(self)
+    }
+}
@@ -0,0 +1,295 @@
+//! Deferred work on Zephyr work queues
+//!
+//! Work items run boxed `FnOnce` closures on a work queue thread, either the system work queue or
+//! one started from Rust with `WorkQueue::start`. The k_work API is not available to user mode,
+//! so everything here is kernel-only. Submitting allocates the closure on the Rust heap.
+
+use alloc::boxed::Box;
+use core::ffi::CStr;
+use core::ptr;
+use core::sync::atomic::{AtomicPtr, Ordering};
+use core::time::Duration;
+
+use libc::c_int;
+use zephyr_sys::raw::{k_work, k_work_delayable, k_work_q, k_work_sync};
+
+use super::NegErr;
+use crate::error::Result;
+use crate::thread::KernelStack;
+use crate::time::Timeout;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// A k_work or k_work_delayable paired with the closure it runs next
+///
+/// The kernel struct must be first so the handler can find the closure from the k_work pointer.
+/// This also holds for k_work_delayable, whose first member is its k_work.
+#[repr(C)]
+struct WorkInner<W> {
+    work: W,
+    job: AtomicPtr<Job>,
+}
+
+impl<W> WorkInner<W> {
+    fn new() -> Box<Self> {
+        Box::new(WorkInner {
+            work: unsafe { core::mem::zeroed() },
+            job: AtomicPtr::new(ptr::null_mut()),
+        })
+    }
+
+    /// Swap in the closure to run next, returning the one it replaced if it never ran
+    fn replace_job(&self, job: Option<Job>) -> Option<Job> {
+        let new = job.map_or(ptr::null_mut(), |job| Box::into_raw(Box::new(job)));
+        let old = self.job.swap(new, Ordering::AcqRel);
+        if old.is_null() {
+            None
+        } else {
+            Some(*unsafe { Box::from_raw(old) })
+        }
+    }
+
+    fn work_ptr(&self) -> *mut W {
+        &self.work as *const W as *mut W
+    }
+}
+
+extern "C" fn handler<W>(work: *mut k_work) {
+    let inner = unsafe { &*(work as *const WorkInner<W>) };
+    // None if cancelled after the work was already taken off the queue
+    if let Some(job) = inner.replace_job(None) {
+        job();
+    }
+}
+
+fn submit_result(ret: c_int) -> Result<bool> {
+    // 0: already queued. 1: queued. 2: queued while running.
+    ret.neg_err().map(|ret| ret != 0)
+}
+
+/// Handle to a work queue
+#[derive(Clone, Copy)]
+pub struct WorkQueue(&'static k_work_q);
+
+unsafe impl Send for WorkQueue {}
+unsafe impl Sync for WorkQueue {}
+
+impl WorkQueue {
+    /// The system work queue
+    pub fn system() -> Self {
+        WorkQueue(unsafe { &*ptr::addr_of!(zephyr_sys::raw::k_sys_work_q) })
+    }
+
+    /// Start a new work queue thread. The queue itself is allocated on the heap and never freed.
+    ///
+    /// Unsafe because the stack must not be in use by any other thread, and the queue's thread
+    /// never exits, so the stack can not be used for anything else afterwards.
+    pub unsafe fn start<const N: usize>(
+        stack: &'static KernelStack<N>,
+        priority: c_int,
+        name: Option<&'static CStr>,
+    ) -> Self {
+        let queue: &'static k_work_q = Box::leak(Box::new(core::mem::zeroed()));
+        let queue_ptr = queue as *const _ as *mut _;
+        let mut config: zephyr_sys::raw::k_work_queue_config = core::mem::zeroed();
+        config.name = name.map_or(ptr::null(), |name| name.as_ptr());
+        zephyr_sys::raw::k_work_queue_init(queue_ptr);
+        zephyr_sys::raw::k_work_queue_start(
+            queue_ptr,
+            stack.as_ptr(),
+            stack.size(),
+            priority,
+            &config,
+        );
+        WorkQueue(queue)
+    }
+
+    fn as_ptr(&self) -> *mut k_work_q {
+        self.0 as *const _ as *mut _
+    }
+}
+
+/// A work item running a closure on a work queue
+///
+/// Each submit replaces the closure to run. If the work is already queued, it still runs only
+/// once, with the latest closure. Dropping the work cancels it and waits for it to finish
+/// running.
+pub struct Work {
+    inner: Box<WorkInner<k_work>>,
+}
+
+unsafe impl Send for Work {}
+unsafe impl Sync for Work {}
+
+impl Work {
+    pub fn new() -> Self {
+        let inner = WorkInner::<k_work>::new();
+        unsafe { zephyr_sys::raw::k_work_init(inner.work_ptr(), Some(handler::<k_work>)) };
+        Work { inner }
+    }
+
+    /// Submit to the system work queue. Returns false if the work was already queued. Fails with
+    /// `Busy` if the work is being cancelled, or `NoDevice` if the queue is draining or not
+    /// started.
+    pub fn submit<F: FnOnce() + Send + 'static>(&self, f: F) -> Result<bool> {
+        self.submit_to_queue(WorkQueue::system(), f)
+    }
+
+    /// Submit to the given work queue. Returns false if the work was already queued. Fails like
+    /// `submit`.
+    pub fn submit_to_queue<F: FnOnce() + Send + 'static>(
+        &self,
+        queue: WorkQueue,
+        f: F,
+    ) -> Result<bool> {
+        drop(self.inner.replace_job(Some(Box::new(f))));
+        submit_result(unsafe {
+            zephyr_sys::raw::k_work_submit_to_queue(queue.as_ptr(), self.inner.work_ptr())
+        })
+    }
+
+    /// Cancel if queued and not yet running. Does not wait. Returns true if the work is still
+    /// running.
+    pub fn cancel(&self) -> bool {
+        let busy = unsafe { zephyr_sys::raw::k_work_cancel(self.inner.work_ptr()) };
+        if busy == 0 {
+            drop(self.inner.replace_job(None));
+        }
+        busy != 0
+    }
+
+    /// Cancel and wait for the work to finish if it is running. Returns true if the work was
+    /// queued or running.
+    pub fn cancel_sync(&self) -> bool {
+        let mut sync: k_work_sync = unsafe { core::mem::zeroed() };
+        let busy = unsafe { zephyr_sys::raw::k_work_cancel_sync(self.inner.work_ptr(), &mut sync) };
+        drop(self.inner.replace_job(None));
+        busy
+    }
+
+    /// Wait for queued or running work to finish. Returns true if it had to wait.
+    pub fn flush(&self) -> bool {
+        let mut sync: k_work_sync = unsafe { core::mem::zeroed() };
+        unsafe { zephyr_sys::raw::k_work_flush(self.inner.work_ptr(), &mut sync) }
+    }
+
+    /// Whether the work is queued or running
+    pub fn is_busy(&self) -> bool {
+        unsafe { zephyr_sys::raw::k_work_busy_get(self.inner.work_ptr()) != 0 }
+    }
+}
+
+impl Drop for Work {
+    fn drop(&mut self) {
+        self.cancel_sync();
+    }
+}
+
+/// A work item running a closure on a work queue after a delay
+///
+/// Like `Work`, each schedule replaces the closure to run.
+pub struct DelayableWork {
+    inner: Box<WorkInner<k_work_delayable>>,
+}
+
+unsafe impl Send for DelayableWork {}
+unsafe impl Sync for DelayableWork {}
+
+impl DelayableWork {
+    pub fn new() -> Self {
+        let inner = WorkInner::<k_work_delayable>::new();
+        unsafe {
+            zephyr_sys::raw::k_work_init_delayable(
+                inner.work_ptr(),
+                Some(handler::<k_work_delayable>),
+            )
+        };
+        DelayableWork { inner }
+    }
+
+    /// Schedule on the system work queue unless already scheduled or queued, in which case the
+    /// existing delay is kept. Returns false if it was already scheduled or queued. Fails like
+    /// `Work::submit`.
+    pub fn schedule<F: FnOnce() + Send + 'static>(&self, delay: Duration, f: F) -> Result<bool> {
+        self.schedule_for_queue(WorkQueue::system(), delay, f)
+    }
+
+    /// Schedule on the given work queue unless already scheduled or queued, in which case the
+    /// existing delay is kept. Returns false if it was already scheduled or queued.
+    pub fn schedule_for_queue<F: FnOnce() + Send + 'static>(
+        &self,
+        queue: WorkQueue,
+        delay: Duration,
+        f: F,
+    ) -> Result<bool> {
+        drop(self.inner.replace_job(Some(Box::new(f))));
+        submit_result(unsafe {
+            zephyr_sys::raw::k_work_schedule_for_queue(
+                queue.as_ptr(),
+                self.inner.work_ptr(),
+                Timeout::from(&delay).0,
+            )
+        })
+    }
+
+    /// Schedule on the system work queue, replacing any existing delay
+    pub fn reschedule<F: FnOnce() + Send + 'static>(&self, delay: Duration, f: F) -> Result<()> {
+        self.reschedule_for_queue(WorkQueue::system(), delay, f)
+    }
+
+    /// Schedule on the given work queue, replacing any existing delay
+    pub fn reschedule_for_queue<F: FnOnce() + Send + 'static>(
+        &self,
+        queue: WorkQueue,
+        delay: Duration,
+        f: F,
+    ) -> Result<()> {
+        drop(self.inner.replace_job(Some(Box::new(f))));
+        submit_result(unsafe {
+            zephyr_sys::raw::k_work_reschedule_for_queue(
+                queue.as_ptr(),
+                self.inner.work_ptr(),
+                Timeout::from(&delay).0,
+            )
+        })
+        .map(|_| ())
+    }
+
+    /// Cancel if scheduled, or queued and not yet running. Does not wait. Returns true if the
+    /// work is still running.
+    pub fn cancel(&self) -> bool {
+        let busy = unsafe { zephyr_sys::raw::k_work_cancel_delayable(self.inner.work_ptr()) };
+        if busy == 0 {
+            drop(self.inner.replace_job(None));
+        }
+        busy != 0
+    }
+
+    /// Cancel and wait for the work to finish if it is running. Returns true if the work was
+    /// scheduled, queued or running.
+    pub fn cancel_sync(&self) -> bool {
+        let mut sync: k_work_sync = unsafe { core::mem::zeroed() };
+        let busy = unsafe {
+            zephyr_sys::raw::k_work_cancel_delayable_sync(self.inner.work_ptr(), &mut sync)
+        };
+        drop(self.inner.replace_job(None));
+        busy
+    }
+
+    /// Run scheduled work immediately and wait for it to finish. Returns true if it had to wait.
+    pub fn flush(&self) -> bool {
+        let mut sync: k_work_sync = unsafe { core::mem::zeroed() };
+        unsafe { zephyr_sys::raw::k_work_flush_delayable(self.inner.work_ptr(), &mut sync) }
+    }
+
+    /// Whether the work is scheduled, queued or running
+    pub fn is_busy(&self) -> bool {
+        unsafe { zephyr_sys::raw::k_work_delayable_busy_get(self.inner.work_ptr()) != 0 }
+    }
+}
+
+impl Drop for DelayableWork {
+    fn drop(&mut self) {
+        self.cancel_sync();
+    }
+}
@@ -2,7 +2,7 @@
 
 /// Ported from z_tmcvt inline C code.
 #[inline(always)]
-pub fn z_tmcvt(
+pub const fn z_tmcvt(
     mut t: u64,
     from_hz: u32,
     to_hz: u32,
@@ -21,7 +21,7 @@ pub fn z_tmcvt(
 
     if !mul_ratio {
         let rdivisor: u32 = if div_ratio { from_hz / to_hz } else { from_hz };
-        let rdivisor: u64 = rdivisor.into();
+        let rdivisor = rdivisor as u64;
 
         if round_up {
             off = rdivisor - 1;
@@ -37,11 +37,11 @@ pub fn z_tmcvt(
      */
     if div_ratio {
         t += off;
-        t / u64::from(from_hz / to_hz)
+        t / (from_hz / to_hz) as u64
     } else if mul_ratio {
-        t * u64::from(to_hz / from_hz)
+        t * (to_hz / from_hz) as u64
     } else {
-        (t * u64::from(to_hz) + off) / u64::from(from_hz)
+        (t * to_hz as u64 + off) / from_hz as u64
     }
 }
 
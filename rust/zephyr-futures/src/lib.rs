@@ -18,6 +18,7 @@ use zephyr_core::mutex::*;
 use zephyr_core::poll::*;
 use zephyr_core::semaphore::*;
 use zephyr_core::thread::{ThreadId, ThreadSyscalls};
+use zephyr_core::timer::*;
 use zephyr_core::Timeout;
 
 pub mod delay;
@@ -234,6 +235,17 @@ impl Executor {
         ExecutorHandle(Arc::downgrade(&self.state))
     }
 
+    /// Spawn `future` and run until it and every other task have completed
+    pub fn block_on<C, F>(&mut self, future: F)
+    where
+        C: MutexSyscalls + KPollSignalSyscalls + PollSyscalls + ThreadSyscalls,
+        F: Future<Output = ()> + 'static,
+    {
+        self.spawn_local_obj(Box::new(future).into())
+            .expect("executor spawn");
+        self.run::<C>();
+    }
+
     pub fn run<C: MutexSyscalls + KPollSignalSyscalls + PollSyscalls + ThreadSyscalls>(&mut self) {
         let reactor = Reactor::new(self.state.thread_signal);
         let current = C::k_current_get();
@@ -246,12 +258,12 @@ impl Executor {
                 // Signal indicates need to poll run queue. Reset before poll.
                 self.state.thread_signal.reset::<C>();
                 loop {
-                    match self.state.inner.lock::<C>().get_runnable() {
+                    match self.state.inner.lock::<C>().unwrap().get_runnable() {
                         Poll::Ready(Some(task)) => {
                             let waker = futures::task::waker_ref(&task);
                             let mut context = Context::from_waker(&*waker);
                             if let Poll::Ready(()) = unsafe { task.poll(&mut context) } {
-                                self.state.inner.lock::<C>().remove_task(task);
+                                self.state.inner.lock::<C>().unwrap().remove_task(task);
                             }
                         }
                         Poll::Pending => break,
@@ -283,7 +295,7 @@ impl LocalSpawn for Executor {
             self.state.thread_signal,
             C::k_current_get(),
         ));
-        self.state.inner.lock::<C>().add_task(task);
+        self.state.inner.lock::<C>().unwrap().add_task(task);
         Ok(())
     }
 }
@@ -293,7 +305,7 @@ impl LocalSpawn for ExecutorHandle {
         use zephyr::context::Any as C;
         if let Some(state) = self.0.upgrade() {
             let task = Arc::new(Task::new(future, state.thread_signal, C::k_current_get()));
-            state.inner.lock::<C>().add_task(task);
+            state.inner.lock::<C>().unwrap().add_task(task);
             Ok(())
         } else {
             Err(SpawnError::shutdown())
@@ -313,7 +325,7 @@ impl Stream for SemaphoreStream {
     type Item = ();
 
     fn poll_next(self: Pin<&mut Self>, context: &mut Context) -> Poll<Option<Self::Item>> {
-        if self.0.try_take::<zephyr::context::Any>() {
+        if self.0.try_take::<zephyr::context::Any>().is_ok() {
             Poll::Ready(Some(()))
         } else {
             REACTOR.with(|r| {
@@ -326,3 +338,40 @@ impl Stream for SemaphoreStream {
         }
     }
 }
+
+/// Stream of timer expirations
+///
+/// The timer must raise `signal` when it expires, as set up by `Timer::init_with_signal`. Each
+/// item is the number of times the timer expired since the previous item.
+pub struct TimerStream {
+    timer: &'static KTimer,
+    signal: &'static KPollSignal,
+}
+
+impl TimerStream {
+    pub fn new(timer: &'static KTimer, signal: &'static KPollSignal) -> Self {
+        TimerStream { timer, signal }
+    }
+}
+
+impl Stream for TimerStream {
+    type Item = u32;
+
+    fn poll_next(self: Pin<&mut Self>, context: &mut Context) -> Poll<Option<Self::Item>> {
+        use zephyr::context::Any as C;
+        // Reset before reading the status so an expiry in between raises it again
+        self.signal.reset::<C>();
+        match self.timer.status_get::<C>() {
+            0 => {
+                REACTOR.with(|r| {
+                    r.borrow_mut()
+                        .as_mut()
+                        .expect("polled timer outside of reactor context")
+                        .register(self.signal, context);
+                });
+                Poll::Pending
+            }
+            count => Poll::Ready(Some(count)),
+        }
+    }
+}
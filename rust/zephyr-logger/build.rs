@@ -0,0 +1,8 @@
+fn main() {
+    // Only set with CONFIG_LOG. Defaults to CONFIG_LOG_DEFAULT_LEVEL.
+    if let Ok(level) = std::env::var("CONFIG_RUST_LOG_LEVEL") {
+        if !level.is_empty() {
+            println!("cargo:rustc-cfg=rust_log_level=\"{}\"", level);
+        }
+    }
+}
@@ -1,23 +1,311 @@
-use log::{LevelFilter, Metadata, Record};
+//! `log` backend for Zephyr, usable without std
+//!
+//! Records are formatted into a fixed buffer on the stack and either printed to the console with
+//! `k_str_out`, or forwarded to the Zephyr log core. `init` and `init_log_core` install a logger
+//! for everything. A `ZephyrLogger` static can set levels per target:
+//!
+//! ```ignore
+//! static LOGGER: ZephyrLogger = ZephyrLogger::console()
+//!     .with_targets(&[("app::net", LevelFilter::Warn), ("app::sensor", LevelFilter::Debug)]);
+//!
+//! LOGGER.init(LevelFilter::Info);
+//! ```
+//!
+//! Records sent to the log core come from the `rust` log module, with the target prefixed to the
+//! message. Targets can instead be mapped to modules registered in C with `LOG_MODULE_REGISTER`,
+//! so the log core's per-module filtering and output can tell them apart:
+//!
+//! ```ignore
+//! // LOG_MODULE_REGISTER(app_net); in a C file
+//! static LOGGER: ZephyrLogger = ZephyrLogger::log_core().with_sources(&[("app::net", "app_net")]);
+//! ```
+#![no_std]
 
+extern crate zephyr_core;
+
+use core::ffi::{c_char, c_int};
+use core::fmt::{self, Write};
+use core::sync::atomic::{AtomicI32, AtomicUsize, Ordering};
+
+use log::{Level, LevelFilter, Metadata, Record};
+
+/// Max level that can be logged, from CONFIG_RUST_LOG_LEVEL, which defaults to
+/// CONFIG_LOG_DEFAULT_LEVEL. Trace is logged at debug level. Everything without CONFIG_LOG.
+///
+/// Only a runtime cap on the level `init` passes to `log::set_max_level`. Records above it are
+/// still compiled in, as Kconfig does not set the log crate's `max_level_*` features. Enable
+/// those in the app's Cargo.toml to compile them out.
+pub const STATIC_MAX_LEVEL: LevelFilter = if cfg!(rust_log_level = "0") {
+    LevelFilter::Off
+} else if cfg!(rust_log_level = "1") {
+    LevelFilter::Error
+} else if cfg!(rust_log_level = "2") {
+    LevelFilter::Warn
+} else if cfg!(rust_log_level = "3") {
+    LevelFilter::Info
+} else {
+    LevelFilter::Trace
+};
+
+/// Size of the buffer a record is formatted into. Longer console output is written in chunks.
+/// Longer messages to the log core are truncated.
+const BUF_SIZE: usize = 128;
+
+/// Most targets a logger can map to log modules
+pub const MAX_SOURCES: usize = 8;
+
+/// Source ID of a mapped module before `init` looks it up, or if it is not registered
+#[allow(clippy::declare_interior_mutable_const)]
+const NO_SOURCE: AtomicI32 = AtomicI32::new(-1);
+
+/// Print log records to the console with `k_str_out`
 pub fn init(max_level: LevelFilter) {
-    log::set_logger(&ZEPHYR_LOGGER).unwrap();
-    log::set_max_level(max_level);
+    CONSOLE_LOGGER.init(max_level);
+}
+
+/// Forward log records to the Zephyr log core, so they go through the same backends and runtime
+/// filters as C logs. Records are logged by the `rust` log module, prefixed with their target.
+/// A `ZephyrLogger` with `with_sources` can log targets from their own modules instead. Without
+/// CONFIG_LOG, nothing is logged.
+pub fn init_log_core(max_level: LevelFilter) {
+    LOG_CORE_LOGGER.init(max_level);
 }
 
-static ZEPHYR_LOGGER: ZephyrLogger = ZephyrLogger;
-struct ZephyrLogger;
+static CONSOLE_LOGGER: ZephyrLogger = ZephyrLogger::console();
+static LOG_CORE_LOGGER: ZephyrLogger = ZephyrLogger::log_core();
+
+#[derive(Clone, Copy)]
+enum Output {
+    Console,
+    LogCore,
+}
+
+/// Logger with levels that can be overridden per target
+pub struct ZephyrLogger {
+    output: Output,
+    targets: &'static [(&'static str, LevelFilter)],
+    sources: &'static [(&'static str, &'static str)],
+    source_ids: [AtomicI32; MAX_SOURCES],
+    max_level: AtomicUsize,
+}
+
+impl ZephyrLogger {
+    /// Print records to the console with `k_str_out`
+    pub const fn console() -> Self {
+        Self::new(Output::Console)
+    }
+
+    /// Forward records to the Zephyr log core
+    pub const fn log_core() -> Self {
+        Self::new(Output::LogCore)
+    }
+
+    const fn new(output: Output) -> Self {
+        ZephyrLogger {
+            output,
+            targets: &[],
+            sources: &[],
+            source_ids: [NO_SOURCE; MAX_SOURCES],
+            max_level: AtomicUsize::new(0),
+        }
+    }
+
+    /// Levels for targets, overriding the max level passed to `init`. A target matches its own
+    /// records and those of its submodules. The first match is used.
+    pub const fn with_targets(self, targets: &'static [(&'static str, LevelFilter)]) -> Self {
+        ZephyrLogger { targets, ..self }
+    }
+
+    /// Log module names for targets, matched like `with_targets`. A matching record is logged by
+    /// that module, which must be registered in C with `LOG_MODULE_REGISTER`. Records for other
+    /// targets, or for a module that is not registered, are logged by the `rust` module. Only
+    /// used when forwarding to the log core. At most `MAX_SOURCES` targets can be mapped.
+    pub const fn with_sources(self, sources: &'static [(&'static str, &'static str)]) -> Self {
+        assert!(sources.len() <= MAX_SOURCES, "too many log sources");
+        ZephyrLogger { sources, ..self }
+    }
+
+    /// Install this logger. Panics if a logger was already installed.
+    pub fn init(&'static self, max_level: LevelFilter) {
+        let max_level = max_level.min(STATIC_MAX_LEVEL);
+        self.max_level.store(max_level as usize, Ordering::Relaxed);
+        if let Output::LogCore = self.output {
+            for (&(_, module), id) in self.sources.iter().zip(&self.source_ids) {
+                let mut name = Buf::new();
+                let _ = name.write_str(module);
+                let source_id = unsafe { rust_log_source_id(name.as_c_str()) };
+                id.store(source_id, Ordering::Relaxed);
+            }
+        }
+        log::set_logger(self).unwrap();
+        // The log macros filter on the highest level any target can log
+        let highest = self
+            .targets
+            .iter()
+            .map(|&(_, level)| level.min(STATIC_MAX_LEVEL))
+            .fold(max_level, Ord::max);
+        log::set_max_level(highest);
+    }
+
+    fn target_level(&self, target: &str) -> LevelFilter {
+        for &(prefix, level) in self.targets {
+            if target_matches(target, prefix) {
+                return level.min(STATIC_MAX_LEVEL);
+            }
+        }
+        LEVELS[self.max_level.load(Ordering::Relaxed)]
+    }
+
+    /// Source ID of the module mapped to the target, or -1 for the `rust` module
+    fn source_id(&self, target: &str) -> c_int {
+        for (&(prefix, _), id) in self.sources.iter().zip(&self.source_ids) {
+            if target_matches(target, prefix) {
+                return id.load(Ordering::Relaxed);
+            }
+        }
+        -1
+    }
+}
+
+/// Whether `target` is `prefix` or one of its submodules
+fn target_matches(target: &str, prefix: &str) -> bool {
+    target.starts_with(prefix)
+        && (target.len() == prefix.len() || target[prefix.len()..].starts_with("::"))
+}
+
+const LEVELS: [LevelFilter; 6] = [
+    LevelFilter::Off,
+    LevelFilter::Error,
+    LevelFilter::Warn,
+    LevelFilter::Info,
+    LevelFilter::Debug,
+    LevelFilter::Trace,
+];
 
 impl log::Log for ZephyrLogger {
     fn enabled(&self, metadata: &Metadata) -> bool {
-        metadata.level() <= log::max_level()
+        metadata.level() <= self.target_level(metadata.target())
     }
 
     fn log(&self, record: &Record) {
-        if self.enabled(record.metadata()) {
-            println!("{} {}: {}", record.level(), record.target(), record.args());
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        match self.output {
+            Output::Console => {
+                let mut out = Console(Buf::new());
+                let _ = writeln!(
+                    out,
+                    "{} {}: {}",
+                    record.level(),
+                    record.target(),
+                    record.args()
+                );
+                out.flush();
+            }
+            Output::LogCore => {
+                let source_id = self.source_id(record.target());
+                let mut out = Buf::new();
+                // The module names the target when it has its own
+                let _ = if source_id < 0 {
+                    write!(out, "{}: {}", record.target(), record.args())
+                } else {
+                    write!(out, "{}", record.args())
+                };
+                let level = match record.level() {
+                    Level::Error => LOG_LEVEL_ERR,
+                    Level::Warn => LOG_LEVEL_WRN,
+                    Level::Info => LOG_LEVEL_INF,
+                    Level::Debug | Level::Trace => LOG_LEVEL_DBG,
+                };
+                unsafe { rust_log_write(level, source_id, out.as_c_str()) };
+            }
         }
     }
 
+    // Nothing is buffered between records. The log core flushes deferred messages itself.
     fn flush(&self) {}
 }
+
+/// Fixed buffer on the stack, with room for a nul terminator. Truncates what does not fit.
+struct Buf {
+    buf: [u8; BUF_SIZE],
+    len: usize,
+}
+
+impl Buf {
+    fn new() -> Self {
+        Buf {
+            buf: [0; BUF_SIZE],
+            len: 0,
+        }
+    }
+
+    fn remaining(&self) -> usize {
+        BUF_SIZE - 1 - self.len
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    /// Append as much of `bytes` as fits, returning how much that was
+    fn push(&mut self, bytes: &[u8]) -> usize {
+        let n = bytes.len().min(self.remaining());
+        self.buf[self.len..self.len + n].copy_from_slice(&bytes[..n]);
+        self.len += n;
+        n
+    }
+
+    fn as_c_str(&mut self) -> *const c_char {
+        self.buf[self.len] = 0;
+        self.buf.as_ptr() as *const c_char
+    }
+}
+
+impl Write for Buf {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.push(s.as_bytes()) < s.len() {
+            Err(fmt::Error)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Writes a full buffer to the console and continues from the start
+struct Console(Buf);
+
+impl Console {
+    fn flush(&mut self) {
+        zephyr_core::any::k_str_out_raw(self.0.as_bytes());
+        self.0.len = 0;
+    }
+}
+
+impl Write for Console {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        // Split on bytes. A character split between writes is still output whole.
+        let mut bytes = s.as_bytes();
+        while !bytes.is_empty() {
+            if self.0.remaining() == 0 {
+                self.flush();
+            }
+            let n = self.0.push(bytes);
+            bytes = &bytes[n..];
+        }
+        Ok(())
+    }
+}
+
+// Defined in rust-log.c
+extern "C" {
+    fn rust_log_source_id(name: *const c_char) -> c_int;
+    fn rust_log_write(level: c_int, source_id: c_int, msg: *const c_char);
+}
+
+// Zephyr LOG_LEVEL_* values
+const LOG_LEVEL_ERR: c_int = 1;
+const LOG_LEVEL_WRN: c_int = 2;
+const LOG_LEVEL_INF: c_int = 3;
+const LOG_LEVEL_DBG: c_int = 4;
@@ -1,7 +1,7 @@
 extern crate proc_macro;
 
 use proc_macro::TokenStream;
-use proc_macro2::{Ident, Literal, TokenTree};
+use proc_macro2::{Delimiter, Ident, Literal, Spacing, Span, TokenTree};
 use quote::quote;
 
 fn get_single_arg(item: TokenStream) -> Ident {
@@ -17,65 +17,192 @@ fn get_single_arg(item: TokenStream) -> Ident {
 
 #[proc_macro]
 pub fn k_mutex_define(item: TokenStream) -> TokenStream {
-    let ident = get_single_arg(item);
+    mutex_define(get_single_arg(item)).into()
+}
+
+fn mutex_define(ident: Ident) -> proc_macro2::TokenStream {
     let section = Literal::string(&format!("._k_mutex.static.{}", ident));
     let ctor = Ident::new(&format!("_rust_mutex_init_{}", ident), ident.span());
     let ctor_ptr = Ident::new(&format!("_ctor_rust_mutex_init_{}", ident), ident.span());
+    let expanded = quote! {
+        // The static storage for the object, itself, initialized at compile time or by a
+        // constructor
+        zephyr::kobj_static! {
+            #section,
+            #ident: zephyr::mutex::global::k_mutex =
+                zephyr::mutex::global::k_mutex::initializer(core::ptr::addr_of!(#ident)),
+            #ctor_ptr = #ctor {
+                use zephyr::mutex::RawMutex;
+                unsafe { #ident.init::<zephyr::context::Kernel>() }
+            }
+        }
+    };
+
+    expanded
+}
+
+/// `k_rwlock_define!(NAME)` defines `NAME: RawRwLock<'static>` to back a `zephyr::rwlock::RwLock`,
+/// along with its kernel mutex and semaphore
+#[proc_macro]
+pub fn k_rwlock_define(item: TokenStream) -> TokenStream {
+    let ident = get_single_arg(item);
+    let mutex = Ident::new(&format!("_rust_rwlock_mutex_{}", ident), ident.span());
+    let sem = Ident::new(&format!("_rust_rwlock_sem_{}", ident), ident.span());
+    let mutex_section = Literal::string(&format!("._k_mutex.static.{}", mutex));
+    let sem_section = Literal::string(&format!("._k_sem.static.{}", sem));
+    let mutex_ctor = Ident::new(&format!("_rust_rwlock_mutex_init_{}", ident), ident.span());
+    let mutex_ctor_ptr = Ident::new(
+        &format!("_ctor_rust_rwlock_mutex_init_{}", ident),
+        ident.span(),
+    );
+    let sem_ctor = Ident::new(&format!("_rust_rwlock_sem_init_{}", ident), ident.span());
+    let sem_ctor_ptr = Ident::new(
+        &format!("_ctor_rust_rwlock_sem_init_{}", ident),
+        ident.span(),
+    );
+    let expanded = quote! {
+        // The static storage for the kernel objects
+        zephyr::kobj_static! {
+            #mutex_section,
+            #mutex: zephyr::mutex::global::k_mutex =
+                zephyr::mutex::global::k_mutex::initializer(core::ptr::addr_of!(#mutex)),
+            #mutex_ctor_ptr = #mutex_ctor {
+                use zephyr::mutex::RawMutex;
+                unsafe { #mutex.init::<zephyr::context::Kernel>() }
+            }
+        }
+        zephyr::kobj_static! {
+            #sem_section,
+            #sem: zephyr::semaphore::global::k_sem =
+                zephyr::semaphore::global::k_sem::initializer(core::ptr::addr_of!(#sem), 0, 1),
+            #sem_ctor_ptr = #sem_ctor {
+                use zephyr::semaphore::Semaphore;
+                unsafe { #sem.init::<zephyr::context::Kernel>(0, 1) }.expect("k_rwlock_define")
+            }
+        }
+
+        static #ident: zephyr::rwlock::RawRwLock<'static> =
+            unsafe { zephyr::rwlock::RawRwLock::new(#mutex.kobj(), #sem.kobj()) };
+    };
+
+    expanded.into()
+}
+
+#[proc_macro]
+pub fn k_condvar_define(item: TokenStream) -> TokenStream {
+    let ident = get_single_arg(item);
+    let section = Literal::string(&format!("._k_condvar.static.{}", ident));
+    let ctor = Ident::new(&format!("_rust_condvar_init_{}", ident), ident.span());
+    let ctor_ptr = Ident::new(&format!("_ctor_rust_condvar_init_{}", ident), ident.span());
+    let expanded = quote! {
+        // The static storage for the object, itself, initialized at compile time or by a
+        // constructor
+        zephyr::kobj_static! {
+            #section,
+            #ident: zephyr::condvar::global::k_condvar =
+                zephyr::condvar::global::k_condvar::initializer(core::ptr::addr_of!(#ident)),
+            #ctor_ptr = #ctor {
+                use zephyr::condvar::Condvar;
+                unsafe { #ident.init::<zephyr::context::Kernel>() }
+            }
+        }
+    };
+
+    expanded.into()
+}
+
+/// `k_timer_define!(NAME)` or `k_timer_define!(NAME, expiry)` where `expiry` is a function or
+/// static closure run from the timer interrupt on each expiry
+#[proc_macro]
+pub fn k_timer_define(item: TokenStream) -> TokenStream {
+    let args = split_args(item);
+    if args.is_empty() || args.len() > 2 {
+        panic!("k_timer_define takes 1 or 2 comma-separated arguments: name, optional expiry fn");
+    }
+    let ident = get_ident(&args[0]).expect("k_timer_define: first argument must be an identifier");
+    let init = match args.get(1) {
+        Some(expiry) => quote! { #ident.init_with_expiry(&#expiry) },
+        None => quote! { #ident.init() },
+    };
+
+    let section = Literal::string(&format!("._k_timer.static.{}", ident));
+    let ctor = Ident::new(&format!("_rust_timer_init_{}", ident), ident.span());
+    let ctor_ptr = Ident::new(&format!("_ctor_rust_timer_init_{}", ident), ident.span());
     let expanded = quote! {
         // The static storage for the object, itself
         #[link_section = #section]
-        static #ident: zephyr::mutex::global::k_mutex = unsafe { zephyr::mutex::global::k_mutex::uninit() };
+        static #ident: zephyr::timer::global::k_timer = unsafe { zephyr::timer::global::k_timer::uninit() };
 
         // A constructor function that calls its init
         #[allow(non_snake_case)]
         extern "C" fn #ctor() {
-            use zephyr::mutex::RawMutex;
-            unsafe { #ident.init::<zephyr::context::Kernel>() }
+            use zephyr::timer::Timer;
+            unsafe { #init }
         }
 
         // Add a pointer to the constructor to .ctors table
-        #[used]
-        #[link_section = ".ctors"]
-        #[allow(non_upper_case_globals)]
-        static #ctor_ptr: extern "C" fn() = #ctor;
+        zephyr::kobj_ctor!(#ctor_ptr = #ctor);
     };
 
     expanded.into()
 }
 
 #[proc_macro]
-pub fn k_poll_signal_define(item: TokenStream) -> TokenStream {
+pub fn k_event_define(item: TokenStream) -> TokenStream {
     let ident = get_single_arg(item);
-    // Using mutex section because there is not one for poll_signal. Need to
-    // ensure this is in kernel memory.
-    let section = Literal::string(&format!("._k_mutex.static.{}", ident));
-    let ctor = Ident::new(&format!("_rust_poll_signal_init_{}", ident), ident.span());
-    let ctor_ptr = Ident::new(
-        &format!("_ctor_rust_poll_signal_init_{}", ident),
-        ident.span(),
-    );
+    let section = Literal::string(&format!("._k_event.static.{}", ident));
+    let ctor = Ident::new(&format!("_rust_event_init_{}", ident), ident.span());
+    let ctor_ptr = Ident::new(&format!("_ctor_rust_event_init_{}", ident), ident.span());
     let expanded = quote! {
         // The static storage for the object, itself
         #[link_section = #section]
-        static #ident: zephyr::poll::global::k_poll_signal = unsafe { zephyr::poll::global::k_poll_signal::uninit() };
+        static #ident: zephyr::event::global::k_event = unsafe { zephyr::event::global::k_event::uninit() };
 
         // A constructor function that calls its init
         #[allow(non_snake_case)]
         extern "C" fn #ctor() {
-            use zephyr::poll::*;
+            use zephyr::event::Event;
             unsafe { #ident.init::<zephyr::context::Kernel>() }
         }
 
         // Add a pointer to the constructor to .ctors table
-        #[used]
-        #[link_section = ".ctors"]
-        #[allow(non_upper_case_globals)]
-        static #ctor_ptr: extern "C" fn() = #ctor;
+        zephyr::kobj_ctor!(#ctor_ptr = #ctor);
     };
 
     expanded.into()
 }
 
+#[proc_macro]
+pub fn k_poll_signal_define(item: TokenStream) -> TokenStream {
+    poll_signal_define(get_single_arg(item)).into()
+}
+
+fn poll_signal_define(ident: Ident) -> proc_macro2::TokenStream {
+    // Using mutex section because there is not one for poll_signal. Need to
+    // ensure this is in kernel memory.
+    let section = Literal::string(&format!("._k_mutex.static.{}", ident));
+    let ctor = Ident::new(&format!("_rust_poll_signal_init_{}", ident), ident.span());
+    let ctor_ptr = Ident::new(
+        &format!("_ctor_rust_poll_signal_init_{}", ident),
+        ident.span(),
+    );
+    let expanded = quote! {
+        // The static storage for the object, itself, initialized at compile time or by a
+        // constructor
+        zephyr::kobj_static! {
+            #section,
+            #ident: zephyr::poll::global::k_poll_signal =
+                zephyr::poll::global::k_poll_signal::initializer(core::ptr::addr_of!(#ident)),
+            #ctor_ptr = #ctor {
+                use zephyr::poll::*;
+                unsafe { #ident.init::<zephyr::context::Kernel>() }
+            }
+        }
+    };
+
+    expanded
+}
+
 fn get_sem_args(item: TokenStream) -> Option<(Ident, Literal, Literal)> {
     let item = proc_macro2::TokenStream::from(item);
     let mut iter = item.into_iter();
@@ -107,23 +234,690 @@ pub fn k_sem_define(item: TokenStream) -> TokenStream {
     let section = Literal::string(&format!("._k_sem.static.{}", ident));
     let ctor = Ident::new(&format!("_rust_sem_init_{}", ident), ident.span());
     let ctor_ptr = Ident::new(&format!("_ctor_rust_sem_init_{}", ident), ident.span());
+    let expanded = quote! {
+        // The static storage for the object, itself, initialized at compile time or by a
+        // constructor
+        zephyr::kobj_static! {
+            #section,
+            #ident: zephyr::semaphore::global::k_sem = zephyr::semaphore::global::k_sem::initializer(
+                core::ptr::addr_of!(#ident),
+                #initial,
+                #limit,
+            ),
+            #ctor_ptr = #ctor {
+                use zephyr::semaphore::*;
+                unsafe { #ident.init::<zephyr::context::Kernel>(#initial, #limit) }
+                    .expect("k_sem_define: invalid initial count or limit")
+            }
+        }
+    };
+
+    expanded.into()
+}
+
+/// Split macro arguments on top-level commas. Commas inside generic arguments of a type, e.g.
+/// `Foo<A, B>`, are not split.
+fn split_args(item: TokenStream) -> Vec<proc_macro2::TokenStream> {
+    let item = proc_macro2::TokenStream::from(item);
+    let mut args = vec![proc_macro2::TokenStream::new()];
+    let mut depth = 0usize;
+    let mut prev_dash = false;
+
+    for tt in item {
+        let mut is_dash = false;
+        if let TokenTree::Punct(ref p) = tt {
+            match p.as_char() {
+                ',' if depth == 0 => {
+                    args.push(proc_macro2::TokenStream::new());
+                    continue;
+                }
+                '<' => depth += 1,
+                // Not the end of generic arguments if part of `->`
+                '>' if !prev_dash => depth = depth.saturating_sub(1),
+                '-' => is_dash = p.spacing() == Spacing::Joint,
+                _ => (),
+            }
+        }
+        prev_dash = is_dash;
+        args.last_mut().unwrap().extend(Some(tt));
+    }
+    // Allow a trailing comma
+    if args.len() > 1 && args.last().unwrap().is_empty() {
+        args.pop();
+    }
+    args
+}
+
+fn get_ident(arg: &proc_macro2::TokenStream) -> Option<Ident> {
+    let mut iter = arg.clone().into_iter();
+    match (iter.next(), iter.next()) {
+        (Some(TokenTree::Ident(ident)), None) => Some(ident),
+        _ => None,
+    }
+}
+
+#[proc_macro]
+pub fn k_msgq_define(item: TokenStream) -> TokenStream {
+    let args = split_args(item);
+    if args.len() != 3 {
+        panic!("k_msgq_define takes 3 comma-separated arguments: name, type, depth");
+    }
+    let ident = get_ident(&args[0]).expect("k_msgq_define: first argument must be an identifier");
+    let msg_type = &args[1];
+    let depth = &args[2];
+
+    let section = Literal::string(&format!("._k_msgq.static.{}", ident));
+    let buf_section = Literal::string(&format!(".noinit._k_msgq_buf.{}", ident));
+    let kobj = Ident::new(&format!("_rust_msgq_{}", ident), ident.span());
+    let buf = Ident::new(&format!("_rust_msgq_buf_{}", ident), ident.span());
+    let ctor = Ident::new(&format!("_rust_msgq_init_{}", ident), ident.span());
+    let ctor_ptr = Ident::new(&format!("_ctor_rust_msgq_init_{}", ident), ident.span());
     let expanded = quote! {
         // The static storage for the object, itself
         #[link_section = #section]
-        static #ident: zephyr::semaphore::global::k_sem = unsafe { zephyr::semaphore::global::k_sem::uninit() };
+        #[allow(non_upper_case_globals)]
+        static #kobj: zephyr::msgq::global::k_msgq = unsafe { zephyr::msgq::global::k_msgq::uninit() };
+
+        // Message storage. Only accessed by the kernel, so keep it out of the Rust partition.
+        #[link_section = #buf_section]
+        #[allow(non_upper_case_globals)]
+        static #buf: zephyr::msgq::MsgqBuffer<#msg_type, { #depth }> = zephyr::msgq::MsgqBuffer::new();
+
+        // Typed handle bound to the object
+        static #ident: zephyr::msgq::MsgQueue<'static, #msg_type> =
+            unsafe { zephyr::msgq::MsgQueue::new(#kobj.kobj()) };
 
         // A constructor function that calls its init
         #[allow(non_snake_case)]
         extern "C" fn #ctor() {
-            use zephyr::semaphore::*;
-            unsafe { #ident.init::<zephyr::context::Kernel>(#initial, #limit) }
+            use zephyr::msgq::RawMsgq;
+            unsafe { #kobj.init::<#msg_type, { #depth }>(&#buf) }
         }
 
         // Add a pointer to the constructor to .ctors table
-        #[used]
-        #[link_section = ".ctors"]
+        zephyr::kobj_ctor!(#ctor_ptr = #ctor);
+    };
+
+    expanded.into()
+}
+
+#[proc_macro]
+pub fn k_pipe_define(item: TokenStream) -> TokenStream {
+    let args = split_args(item);
+    if args.len() != 2 {
+        panic!("k_pipe_define takes 2 comma-separated arguments: name, buffer size");
+    }
+    let ident = get_ident(&args[0]).expect("k_pipe_define: first argument must be an identifier");
+    let size = &args[1];
+
+    let section = Literal::string(&format!("._k_pipe.static.{}", ident));
+    let buf_section = Literal::string(&format!(".noinit._k_pipe_buf.{}", ident));
+    let buf = Ident::new(&format!("_rust_pipe_buf_{}", ident), ident.span());
+    let ctor = Ident::new(&format!("_rust_pipe_init_{}", ident), ident.span());
+    let ctor_ptr = Ident::new(&format!("_ctor_rust_pipe_init_{}", ident), ident.span());
+    let expanded = quote! {
+        // The static storage for the object, itself
+        #[link_section = #section]
+        static #ident: zephyr::pipe::global::k_pipe = unsafe { zephyr::pipe::global::k_pipe::uninit() };
+
+        // Ring buffer. Only accessed by the kernel, so keep it out of the Rust partition.
+        #[link_section = #buf_section]
+        #[allow(non_upper_case_globals)]
+        static #buf: zephyr::pipe::PipeBuffer<{ #size }> = zephyr::pipe::PipeBuffer::new();
+
+        // A constructor function that calls its init
+        #[allow(non_snake_case)]
+        extern "C" fn #ctor() {
+            use zephyr::pipe::Pipe;
+            unsafe { #ident.init(&#buf) }
+        }
+
+        // Add a pointer to the constructor to .ctors table
+        zephyr::kobj_ctor!(#ctor_ptr = #ctor);
+    };
+
+    expanded.into()
+}
+
+fn queue_define(item: TokenStream, macro_name: &str, handle: &str) -> TokenStream {
+    let args = split_args(item);
+    if args.len() != 2 {
+        panic!("{} takes 2 comma-separated arguments: name, type", macro_name);
+    }
+    let ident = get_ident(&args[0])
+        .unwrap_or_else(|| panic!("{}: first argument must be an identifier", macro_name));
+    let item_type = &args[1];
+
+    let handle = Ident::new(handle, Span::call_site());
+    let section = Literal::string(&format!("._k_queue.static.{}", ident));
+    let kobj = Ident::new(&format!("_rust_queue_{}", ident), ident.span());
+    let ctor = Ident::new(&format!("_rust_queue_init_{}", ident), ident.span());
+    let ctor_ptr = Ident::new(&format!("_ctor_rust_queue_init_{}", ident), ident.span());
+    let expanded = quote! {
+        // The static storage for the object, itself
+        #[link_section = #section]
+        #[allow(non_upper_case_globals)]
+        static #kobj: zephyr::fifo::global::k_queue = unsafe { zephyr::fifo::global::k_queue::uninit() };
+
+        // Typed handle bound to the object
+        static #ident: zephyr::fifo::#handle<'static, #item_type> =
+            unsafe { zephyr::fifo::#handle::new(#kobj.kobj()) };
+
+        // A constructor function that calls its init
+        #[allow(non_snake_case)]
+        extern "C" fn #ctor() {
+            use zephyr::fifo::RawQueue;
+            unsafe { #kobj.init::<zephyr::context::Kernel>() }
+        }
+
+        // Add a pointer to the constructor to .ctors table
+        zephyr::kobj_ctor!(#ctor_ptr = #ctor);
+    };
+
+    expanded.into()
+}
+
+#[proc_macro]
+pub fn k_fifo_define(item: TokenStream) -> TokenStream {
+    queue_define(item, "k_fifo_define", "Fifo")
+}
+
+#[proc_macro]
+pub fn k_lifo_define(item: TokenStream) -> TokenStream {
+    queue_define(item, "k_lifo_define", "Lifo")
+}
+
+#[proc_macro]
+pub fn k_mem_slab_define(item: TokenStream) -> TokenStream {
+    let args = split_args(item);
+    if args.len() != 3 {
+        panic!("k_mem_slab_define takes 3 comma-separated arguments: name, type, num_blocks");
+    }
+    let ident =
+        get_ident(&args[0]).expect("k_mem_slab_define: first argument must be an identifier");
+    let item_type = &args[1];
+    let num_blocks = &args[2];
+
+    let section = Literal::string(&format!("._k_mem_slab.static.{}", ident));
+    let buf_section = Literal::string(&format!(".noinit._k_mem_slab_buf.{}", ident));
+    let buffer = Ident::new(&format!("_rust_mem_slab_buf_{}", ident), ident.span());
+    let ctor = Ident::new(&format!("_rust_mem_slab_init_{}", ident), ident.span());
+    let ctor_ptr = Ident::new(&format!("_ctor_rust_mem_slab_init_{}", ident), ident.span());
+    let expanded = quote! {
+        // Only accessed by the kernel, so keep it out of the Rust partition
+        #[link_section = #buf_section]
+        #[allow(non_upper_case_globals)]
+        static #buffer: zephyr::memslab::SlabBuffer<#item_type, { #num_blocks }> =
+            zephyr::memslab::SlabBuffer::new();
+
+        // The slab's free list is kernel data, so it goes with the statically defined slabs
+        #[link_section = #section]
+        static #ident: zephyr::memslab::MemSlab<#item_type> =
+            unsafe { zephyr::memslab::MemSlab::uninit() };
+
+        // A constructor function that calls its init
+        #[allow(non_snake_case)]
+        extern "C" fn #ctor() {
+            unsafe { #ident.init(&#buffer) }.expect("k_mem_slab_define: invalid block size")
+        }
+
+        // Add a pointer to the constructor to .ctors table
+        zephyr::kobj_ctor!(#ctor_ptr = #ctor);
+    };
+
+    expanded.into()
+}
+
+#[proc_macro]
+pub fn k_kernel_stack_define(item: TokenStream) -> TokenStream {
+    let args = split_args(item);
+    if args.len() != 2 {
+        panic!("k_kernel_stack_define takes 2 comma-separated arguments: name, size");
+    }
+    let ident =
+        get_ident(&args[0]).expect("k_kernel_stack_define: first argument must be an identifier");
+
+    kernel_stack_define(ident, &args[1]).into()
+}
+
+fn kernel_stack_define(ident: Ident, size: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let section = Literal::string(&format!(".noinit._k_kernel_stack.{}", ident));
+    quote! {
+        // Only accessed by the kernel, so keep it out of the Rust partition
+        #[link_section = #section]
+        static #ident: zephyr::thread::KernelStack<{ #size + zephyr::thread::KERNEL_STACK_RESERVED }> =
+            zephyr::thread::KernelStack::new();
+    }
+}
+
+/// `k_mem_domain_define!(NAME)` defines an empty memory domain, `NAME: MemDomain<'static>`
+#[proc_macro]
+pub fn k_mem_domain_define(item: TokenStream) -> TokenStream {
+    let ident = get_single_arg(item);
+    let section = Literal::string(&format!(".noinit._k_mem_domain.{}", ident));
+    let storage = Ident::new(&format!("_rust_mem_domain_{}", ident), ident.span());
+    let ctor = Ident::new(&format!("_rust_mem_domain_init_{}", ident), ident.span());
+    let ctor_ptr = Ident::new(&format!("_ctor_rust_mem_domain_init_{}", ident), ident.span());
+    let expanded = quote! {
+        // User threads must not be able to change their permissions, so keep it out of the Rust
+        // partition
+        #[link_section = #section]
         #[allow(non_upper_case_globals)]
-        static #ctor_ptr: extern "C" fn() = #ctor;
+        static #storage: zephyr::memdomain::StaticMemDomain =
+            unsafe { zephyr::memdomain::StaticMemDomain::uninit() };
+
+        static #ident: zephyr::memdomain::MemDomain<'static> = #storage.mem_domain();
+
+        // A constructor function that calls its init
+        #[allow(non_snake_case)]
+        extern "C" fn #ctor() {
+            unsafe { #storage.init::<zephyr::context::Kernel>() }.expect("k_mem_domain_define")
+        }
+
+        // Add a pointer to the constructor to .ctors table
+        zephyr::kobj_ctor!(#ctor_ptr = #ctor);
+    };
+
+    expanded.into()
+}
+
+/// `k_mem_partition_define!(NAME, SIZE, ACCESS)` defines a zeroed `SIZE` byte buffer and a
+/// partition `NAME: MemPartition` covering it with `ACCESS: UserAccess` for user threads.
+///
+/// SIZE must be an integer literal. The buffer is aligned to SIZE, so a power of two meets the
+/// MPU rules on ARM, and a multiple of the page size is also needed with an MMU, e.g. on x86.
+#[proc_macro]
+pub fn k_mem_partition_define(item: TokenStream) -> TokenStream {
+    let args = split_args(item);
+    if args.len() != 3 {
+        panic!("k_mem_partition_define takes 3 comma-separated arguments: name, size, access");
+    }
+    let ident =
+        get_ident(&args[0]).expect("k_mem_partition_define: first argument must be an identifier");
+    let size = match args[1].clone().into_iter().collect::<Vec<_>>().as_slice() {
+        [TokenTree::Literal(size)] => size.clone(),
+        _ => panic!("k_mem_partition_define: size must be an integer literal"),
+    };
+    let access = &args[2];
+
+    let section = Literal::string(&format!(".noinit._k_mem_partition.{}", ident));
+    let buf_section = Literal::string(&format!(".noinit._k_mem_partition_buf.{}", ident));
+    let buf_type = Ident::new(&format!("_RustMemPartitionBuf_{}", ident), ident.span());
+    let buf = Ident::new(&format!("_rust_mem_partition_buf_{}", ident), ident.span());
+    let ctor = Ident::new(&format!("_rust_mem_partition_init_{}", ident), ident.span());
+    let ctor_ptr = Ident::new(
+        &format!("_ctor_rust_mem_partition_init_{}", ident),
+        ident.span(),
+    );
+    let expanded = quote! {
+        #[repr(C, align(#size))]
+        #[allow(non_camel_case_types)]
+        struct #buf_type(core::cell::UnsafeCell<core::mem::MaybeUninit<[u8; #size]>>);
+
+        unsafe impl Sync for #buf_type {}
+
+        // Only accessible to threads in a domain with the partition, so keep it out of the Rust
+        // partition. Zeroed by the constructor.
+        #[link_section = #buf_section]
+        #[allow(non_upper_case_globals)]
+        static #buf: #buf_type =
+            #buf_type(core::cell::UnsafeCell::new(core::mem::MaybeUninit::uninit()));
+
+        #[link_section = #section]
+        static #ident: zephyr::memdomain::MemPartition =
+            unsafe { zephyr::memdomain::MemPartition::uninit() };
+
+        // A constructor function that calls its init
+        #[allow(non_snake_case)]
+        extern "C" fn #ctor() {
+            unsafe {
+                let start = #buf.0.get() as *mut u8;
+                core::ptr::write_bytes(start, 0, #size);
+                #ident.init(start, #size, #access)
+            }
+        }
+
+        // Add a pointer to the constructor to .ctors table
+        zephyr::kobj_ctor!(#ctor_ptr = #ctor);
+    };
+
+    expanded.into()
+}
+
+fn app_mem(attr: TokenStream, item: TokenStream, kind: &str) -> TokenStream {
+    let partition = get_ident(&attr.into())
+        .unwrap_or_else(|| panic!("app_{}mem takes one partition name argument", &kind[..1]));
+    let item = proc_macro2::TokenStream::from(item);
+    let is_static = item.clone().into_iter().any(|tt| match tt {
+        TokenTree::Ident(ref ident) => ident == "static",
+        _ => false,
+    });
+    if !is_static {
+        panic!("app_{}mem can only be applied to a static", &kind[..1]);
+    }
+
+    let section = Literal::string(&format!("data_smem_{}_{}", partition, kind));
+    let expanded = quote! {
+        zephyr::app_mem_static!(#section, #item);
+    };
+
+    expanded.into()
+}
+
+/// `#[app_dmem(PARTITION)]` places an initialized static in an app memory partition, like
+/// K_APP_DMEM in C
+///
+/// The partition is defined in C with K_APPMEM_PARTITION_DEFINE, which is what lets the build
+/// find it, and can be referenced from Rust with `zephyr::static_mem_partition!`. User threads
+/// can access the static once the partition is added to their memory domain. Without
+/// CONFIG_USERSPACE it is an ordinary static.
+#[proc_macro_attribute]
+pub fn app_dmem(attr: TokenStream, item: TokenStream) -> TokenStream {
+    app_mem(attr, item, "data")
+}
+
+/// `#[app_bmem(PARTITION)]` places a zero-initialized static in an app memory partition, like
+/// K_APP_BMEM in C. The static's initializer must be all zeroes, since the section is cleared at
+/// boot rather than loaded. See `app_dmem`.
+#[proc_macro_attribute]
+pub fn app_bmem(attr: TokenStream, item: TokenStream) -> TokenStream {
+    app_mem(attr, item, "bss")
+}
+
+/// Name of a function item and whether it is `async`
+fn get_fn_signature(item: &proc_macro2::TokenStream, macro_name: &str) -> (Ident, bool) {
+    let mut is_async = false;
+    let mut iter = item.clone().into_iter();
+    while let Some(tt) = iter.next() {
+        if let TokenTree::Ident(ref ident) = tt {
+            if ident == "async" {
+                is_async = true;
+            } else if ident == "fn" {
+                match (iter.next(), iter.next()) {
+                    (Some(TokenTree::Ident(name)), Some(TokenTree::Group(ref args)))
+                        if args.delimiter() == Delimiter::Parenthesis =>
+                    {
+                        if !args.stream().is_empty() {
+                            panic!("{} function must not take arguments", macro_name);
+                        }
+                        return (name, is_async);
+                    }
+                    _ => break,
+                }
+            }
+        }
+    }
+    panic!("{} can only be applied to a function", macro_name);
+}
+
+/// `#[zephyr::main]` makes a function the Rust entry point, `rust_main`, which C calls from its
+/// `main`
+///
+/// The `zephyr::panic` hook is installed first, so panics are reported and raised as a Zephyr
+/// fatal error.
+///
+/// Options, separated by commas:
+/// * `log = "level"` sets the max level of the zephyr-logger `log` backend, which is installed
+///   first. Defaults to `"info"`. `"off"` leaves logging to the app.
+/// * `user` runs the function in user mode, after adding the thread to the Rust memory domain.
+/// * `executor` runs an `async fn` in a zephyr-futures `Executor` until it and anything spawned
+///   from it complete. The app must depend on zephyr-futures.
+///
+/// ```ignore
+/// #[zephyr::main(user, executor)]
+/// async fn main() {
+///     zephyr_futures::delay::Delay::new(Duration::from_secs(1)).await;
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn main(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut level = Ident::new("Info", Span::call_site());
+    let mut user = false;
+    let mut executor = false;
+    for arg in split_args(attr) {
+        if arg.is_empty() {
+            continue;
+        }
+        if let Some(ident) = get_ident(&arg) {
+            if ident == "user" {
+                user = true;
+                continue;
+            } else if ident == "executor" {
+                executor = true;
+                continue;
+            }
+        }
+        let mut iter = arg.clone().into_iter();
+        match (iter.next(), iter.next(), iter.next(), iter.next()) {
+            (
+                Some(TokenTree::Ident(ref name)),
+                Some(TokenTree::Punct(ref eq)),
+                Some(TokenTree::Literal(ref value)),
+                None,
+            ) if name == "log" && eq.as_char() == '=' => {
+                let value = value.to_string();
+                level = match value.trim_matches('"') {
+                    "off" => Ident::new("Off", Span::call_site()),
+                    "error" => Ident::new("Error", Span::call_site()),
+                    "warn" => Ident::new("Warn", Span::call_site()),
+                    "info" => Ident::new("Info", Span::call_site()),
+                    "debug" => Ident::new("Debug", Span::call_site()),
+                    "trace" => Ident::new("Trace", Span::call_site()),
+                    _ => panic!("zephyr::main: unknown log level {}", value),
+                };
+            }
+            _ => panic!("zephyr::main: unknown option `{}`", arg),
+        }
+    }
+
+    let item = proc_macro2::TokenStream::from(item);
+    let (name, is_async) = get_fn_signature(&item, "zephyr::main");
+    if is_async != executor {
+        panic!("zephyr::main: an async fn needs the executor option, and the executor an async fn");
+    }
+    let context = if user {
+        quote!(zephyr::context::User)
+    } else {
+        quote!(zephyr::context::Kernel)
+    };
+
+    let mut statics = proc_macro2::TokenStream::new();
+    let mut grants = proc_macro2::TokenStream::new();
+    let run = if executor {
+        let mutex = Ident::new("_rust_main_executor_mutex", Span::call_site());
+        let signal = Ident::new("_rust_main_executor_signal", Span::call_site());
+        statics.extend(mutex_define(mutex.clone()));
+        statics.extend(poll_signal_define(signal.clone()));
+        grants = quote! {
+            current.k_object_access_grant::<zephyr::context::Kernel, _>(&#mutex);
+            current.k_object_access_grant::<zephyr::context::Kernel, _>(&#signal);
+        };
+        quote! {
+            let mut executor = unsafe { zephyr_futures::Executor::new(&#mutex, &#signal) };
+            executor.block_on::<#context, _>(#name());
+        }
+    } else {
+        quote!(#name();)
+    };
+    let run = if user {
+        user_mode_enter(grants, run)
+    } else {
+        run
+    };
+
+    let expanded = quote! {
+        #item
+
+        #statics
+
+        #[no_mangle]
+        pub extern "C" fn rust_main() {
+            zephyr::panic::install();
+            zephyr::rt::init_logger(zephyr::rt::LevelFilter::#level);
+            #run
+        }
+    };
+
+    expanded.into()
+}
+
+/// Drop the current thread to user mode in the Rust memory domain, then `run`
+fn user_mode_enter(
+    grants: proc_macro2::TokenStream,
+    run: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    quote! {
+        use zephyr::thread::ThreadSyscalls;
+        let current = zephyr::context::Kernel::k_current_get();
+        #grants
+        zephyr::static_mem_domain!(rust_std_domain)
+            .add_thread::<zephyr::context::Kernel>(current);
+        zephyr::kernel::k_thread_user_mode_enter(|| { #run });
+    }
+}
+
+/// `#[zephyr::thread(stack = SIZE, priority = PRIO)]` defines a thread running a function, created
+/// by the kernel at boot like K_THREAD_DEFINE. The thread object is a static named after the
+/// function in upper case, so `fn worker()` can be referred to with `WORKER.tid()`.
+///
+/// Options, separated by commas:
+/// * `stack = size` (required) is the stack size in bytes.
+/// * `priority = prio` (required) is the thread priority.
+/// * `c_stack = NAME` runs on a stack defined in C with `K_THREAD_STACK_DEFINE(NAME, size)`
+///   instead of a kernel stack defined in Rust. `size` must match `stack`.
+/// * `user` runs the function in user mode, after adding the thread to the Rust memory domain.
+///   Needs a `c_stack` when user mode is enabled, as a Rust-defined stack is kernel-only.
+/// * `options = bits` are thread options from `zephyr::thread::options`. Defaults to 0.
+/// * `delay = ms` delays the start of the thread. `delay = forever` leaves it for
+///   `k_thread_start`. Defaults to 0.
+///
+/// ```ignore
+/// #[zephyr::thread(stack = 1024, priority = 5)]
+/// fn worker() {
+///     loop {
+///         zephyr::kernel::k_sleep(Duration::from_secs(1));
+///     }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn thread(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut stack = None;
+    let mut priority = None;
+    let mut c_stack = None;
+    let mut user = false;
+    let mut options = quote!(0);
+    let mut delay = quote!(0);
+    for arg in split_args(attr) {
+        if arg.is_empty() {
+            continue;
+        }
+        if let Some(ident) = get_ident(&arg) {
+            if ident == "user" {
+                user = true;
+                continue;
+            }
+        }
+        let mut iter = arg.clone().into_iter();
+        let (name, value) = match (iter.next(), iter.next()) {
+            (Some(TokenTree::Ident(name)), Some(TokenTree::Punct(ref eq)))
+                if eq.as_char() == '=' =>
+            {
+                (name, iter.collect::<proc_macro2::TokenStream>())
+            }
+            _ => panic!("zephyr::thread: unknown option `{}`", arg),
+        };
+        if value.is_empty() {
+            panic!("zephyr::thread: `{}` needs a value", name);
+        }
+        if name == "stack" {
+            stack = Some(value);
+        } else if name == "priority" {
+            priority = Some(value);
+        } else if name == "c_stack" {
+            c_stack =
+                Some(get_ident(&value).expect("zephyr::thread: c_stack must be an identifier"));
+        } else if name == "options" {
+            options = value;
+        } else if name == "delay" {
+            delay = match get_ident(&value) {
+                Some(ref ident) if ident == "forever" => quote!(-1),
+                _ => value,
+            };
+        } else {
+            panic!("zephyr::thread: unknown option `{}`", arg);
+        }
+    }
+    let stack = stack.expect("zephyr::thread: missing `stack = size`");
+    let priority = priority.expect("zephyr::thread: missing `priority = prio`");
+
+    let item = proc_macro2::TokenStream::from(item);
+    let (name, is_async) = get_fn_signature(&item, "zephyr::thread");
+    if is_async {
+        panic!("zephyr::thread: function must not be async");
+    }
+
+    let thread = Ident::new(&name.to_string().to_uppercase(), Span::call_site());
+    let thread_section = Literal::string(&format!(".noinit._k_thread_obj.{}", thread));
+    let data = Ident::new(&format!("_rust_thread_data_{}", name), Span::call_site());
+    let data_section = Literal::string(&format!("._static_thread_data.static.{}", name));
+    let entry = Ident::new(&format!("_rust_thread_entry_{}", name), Span::call_site());
+    let name_str = Literal::string(&format!("{}\0", name));
+
+    let mut statics = proc_macro2::TokenStream::new();
+    let (stack_ptr, stack_size) = match c_stack {
+        Some(c_stack) => (
+            quote!(zephyr::static_thread_stack!(#c_stack)),
+            quote!({ #stack }),
+        ),
+        None => {
+            let ident = Ident::new(&format!("_rust_thread_stack_{}", name), Span::call_site());
+            statics.extend(kernel_stack_define(ident.clone(), &stack));
+            if user {
+                statics.extend(quote! {
+                    const _: () = zephyr::thread::assert_user_stack(false);
+                });
+            }
+            (quote!(#ident.as_ptr()), quote!(#ident.size()))
+        }
+    };
+
+    let run = if user {
+        user_mode_enter(proc_macro2::TokenStream::new(), quote!(#name();))
+    } else {
+        quote!(#name();)
+    };
+
+    let expanded = quote! {
+        #item
+
+        #statics
+
+        #[link_section = #thread_section]
+        static #thread: zephyr::thread::global::k_thread =
+            unsafe { zephyr::thread::global::k_thread::uninit() };
+
+        extern "C" fn #entry(
+            _: *mut core::ffi::c_void,
+            _: *mut core::ffi::c_void,
+            _: *mut core::ffi::c_void,
+        ) {
+            #run
+        }
+
+        #[used]
+        #[link_section = #data_section]
+        static #data: zephyr::thread::StaticThreadData = unsafe {
+            zephyr::thread::StaticThreadData::new(
+                &#thread,
+                #stack_ptr,
+                #stack_size,
+                #entry,
+                (#priority) as _,
+                (#options) as _,
+                #delay,
+                #name_str,
+            )
+        };
     };
 
     expanded.into()
@@ -11,6 +11,14 @@ pub mod raw {
     unsafe impl Sync for k_mutex {}
     unsafe impl Send for k_sem {}
     unsafe impl Sync for k_sem {}
+    unsafe impl Send for k_msgq {}
+    unsafe impl Sync for k_msgq {}
+    unsafe impl Send for k_pipe {}
+    unsafe impl Sync for k_pipe {}
+    unsafe impl Send for k_queue {}
+    unsafe impl Sync for k_queue {}
+    unsafe impl Send for k_timer {}
+    unsafe impl Sync for k_timer {}
     unsafe impl Send for device {}
     unsafe impl Sync for device {}
 
@@ -25,9 +25,44 @@
 #include <posix/time.h>
 #endif
 
+#ifdef CONFIG_SHELL
+#if KERNEL_VERSION_MAJOR < 3
+#include <shell/shell.h>
+#else
+#include <zephyr/shell/shell.h>
+#endif
+#endif
+
 // Create a constant we can use from Rust in all cases
 #ifdef CONFIG_USERSPACE
 const bool RUST_CONFIG_USERSPACE = true;
 #else
 const bool RUST_CONFIG_USERSPACE = false;
 #endif
+
+// Layout of kernel-only thread stacks allocated in Rust. Kernel stacks exist since Zephyr 2.4.
+#ifdef K_KERNEL_STACK_RESERVED
+const size_t RUST_KERNEL_STACK_RESERVED = K_KERNEL_STACK_RESERVED;
+const size_t RUST_KERNEL_STACK_OBJ_ALIGN = Z_KERNEL_STACK_OBJ_ALIGN;
+#endif
+
+// Priority Z_MUTEX_INITIALIZER gives a mutex without an owner
+const int RUST_K_LOWEST_APPLICATION_THREAD_PRIO = K_LOWEST_APPLICATION_THREAD_PRIO;
+
+// Wrappers for inline functions, defined in rust-inline.c
+unsigned int rust_irq_lock(void);
+void rust_irq_unlock(unsigned int key);
+k_spinlock_key_t rust_k_spin_lock(struct k_spinlock *l);
+void rust_k_spin_unlock(struct k_spinlock *l, k_spinlock_key_t key);
+void rust_k_mem_slab_free(struct k_mem_slab *slab, void *mem);
+uint32_t rust_k_mem_slab_num_used_get(struct k_mem_slab *slab);
+uint32_t rust_k_mem_slab_num_free_get(struct k_mem_slab *slab);
+void rust_k_oops(void);
+void rust_k_panic(void);
+#ifdef CONFIG_SHELL
+void rust_shell_write(const struct shell *sh, const char *data, size_t len);
+#endif
+#ifdef CONFIG_USERSPACE
+void rust_k_mem_partition_init(struct k_mem_partition *part, uintptr_t start,
+                               size_t size, int user_access);
+#endif
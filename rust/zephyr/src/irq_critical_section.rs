@@ -0,0 +1,17 @@
+//! Implementation of the critical-section crate, so crates built on it work on Zephyr
+//!
+//! Critical sections lock interrupts with irq_lock, which nests and on SMP is a global lock
+//! across CPUs. This is kernel-only: entering a critical section from a user mode thread faults.
+
+struct ZephyrCriticalSection;
+critical_section::set_impl!(ZephyrCriticalSection);
+
+unsafe impl critical_section::Impl for ZephyrCriticalSection {
+    unsafe fn acquire() -> critical_section::RawRestoreState {
+        zephyr_sys::raw::rust_irq_lock()
+    }
+
+    unsafe fn release(key: critical_section::RawRestoreState) {
+        zephyr_sys::raw::rust_irq_unlock(key)
+    }
+}
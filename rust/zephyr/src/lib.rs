@@ -4,10 +4,51 @@ extern crate zephyr_sys;
 use std::io;
 
 pub use zephyr_core::*;
+pub use zephyr_macros::{main, thread};
 pub mod device;
 pub mod eeprom;
+#[cfg(feature = "critical-section")]
+mod irq_critical_section;
+pub mod panic;
+pub mod pipe;
 pub mod uart;
 
+/// Conversions between `Error` and `std::io::Error`
+///
+/// Not a replacement for `From`: `?` does not convert between the two. The orphan rule only lets
+/// std implement `From` and `std::error::Error` for `Error`, as zephyr_core is a dependency of
+/// std, so those impls belong in the std port (the rust/rust submodule) and are not there yet.
+pub trait ErrorExt {
+    fn into_io_error(self) -> io::Error;
+    /// Errors without an errno become `Error::Io`
+    fn from_io_error(e: &io::Error) -> Self;
+}
+
+impl ErrorExt for Error {
+    fn into_io_error(self) -> io::Error {
+        io::Error::from_raw_os_error(self.errno() as i32)
+    }
+
+    fn from_io_error(e: &io::Error) -> Self {
+        match e.raw_os_error() {
+            Some(errno) if errno > 0 => Error::from_errno(errno as u32),
+            _ => Error::Io,
+        }
+    }
+}
+
+/// Used by `#[zephyr::main]`
+#[doc(hidden)]
+pub mod rt {
+    pub use log::LevelFilter;
+
+    pub fn init_logger(max_level: LevelFilter) {
+        if max_level != LevelFilter::Off {
+            zephyr_logger::init(max_level);
+        }
+    }
+}
+
 trait NegErrno: NegErr {
     fn neg_errno(&self) -> io::Result<u32>;
     fn zero_or_neg_errno(&self) -> io::Result<()>;
@@ -15,8 +56,7 @@ trait NegErrno: NegErr {
 
 impl NegErrno for i32 {
     fn neg_errno(&self) -> io::Result<u32> {
-        self.neg_err()
-            .map_err(|e| io::Error::from_raw_os_error(e as i32))
+        self.neg_err().map_err(Error::into_io_error)
     }
 
     fn zero_or_neg_errno(&self) -> io::Result<()> {
@@ -0,0 +1,15 @@
+//! Reporting panics and raising a Zephyr fatal error. See `zephyr_core::panic`.
+
+use std::panic::PanicInfo;
+
+pub use zephyr_core::panic::*;
+
+/// Install `zephyr_core::panic::handle` as the std panic hook, so panics are reported with the
+/// current thread and raised as a fatal error instead of aborting. Done by `#[zephyr::main]`.
+pub fn install() {
+    std::panic::set_hook(Box::new(hook));
+}
+
+fn hook(info: &PanicInfo) {
+    handle(info)
+}
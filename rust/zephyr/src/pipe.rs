@@ -0,0 +1,40 @@
+use std::io;
+use std::marker::PhantomData;
+
+pub use zephyr_core::pipe::*;
+
+use crate::{Error, ErrorExt};
+
+/// Adapter implementing `std::io::Read` and `std::io::Write` on a pipe
+///
+/// Reads and writes block until at least one byte is transferred, so this can be wrapped in a
+/// `BufReader` or used with `write_all`.
+pub struct PipeIo<'p, C: PipeSyscalls> {
+    pipe: &'p KPipe,
+    _syscalls: PhantomData<C>,
+}
+
+impl<'p, C: PipeSyscalls> PipeIo<'p, C> {
+    pub fn new(pipe: &'p KPipe) -> Self {
+        PipeIo {
+            pipe,
+            _syscalls: PhantomData,
+        }
+    }
+}
+
+impl<'p, C: PipeSyscalls> io::Read for PipeIo<'p, C> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.pipe.read::<C>(buf).map_err(Error::into_io_error)
+    }
+}
+
+impl<'p, C: PipeSyscalls> io::Write for PipeIo<'p, C> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pipe.write::<C>(buf).map_err(Error::into_io_error)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
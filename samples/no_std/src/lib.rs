@@ -41,7 +41,7 @@ fn mutex_test() {
     // Should allow cloning directly if the data is a reference.
     let _other_mutex = mutex.clone();
     zephyr_core::any::k_str_out("Locking\n");
-    let _val = mutex.lock::<zephyr_core::context::Any>();
+    let _val = mutex.lock::<zephyr_core::context::Any>().unwrap();
     zephyr_core::any::k_str_out("Unlocking\n");
 }
 
@@ -126,8 +126,8 @@ pub extern "C" fn rust_main() {
         for _ in &a[..=(len - 1)] {}
     }
 
-    TLS_SEM.take::<Context>();
-    assert!(!TLS_SEM.try_take::<Context>());
+    TLS_SEM.take::<Context>().unwrap();
+    assert!(TLS_SEM.try_take::<Context>().is_err());
     #[cfg(feature = "have_std")]
     TLS.with(|f| {
         println!("main thread: f = {}\n", *f.borrow());
@@ -156,7 +156,7 @@ pub extern "C" fn rust_main() {
         warn!("TEST: warn!()");
         error!("TEST: error!()");
 
-        assert!(TLS_SEM.try_take::<Context>());
+        assert!(TLS_SEM.try_take::<Context>().is_ok());
         #[cfg(feature = "have_std")]
         TLS.with(|f| {
             println!("main thread: f = {}", *f.borrow());
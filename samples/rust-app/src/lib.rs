@@ -5,13 +5,11 @@ extern crate log;
 
 extern crate zephyr_macros;
 extern crate zephyr;
-extern crate zephyr_logger;
 
 use std::cell::RefCell;
 use std::time::Duration;
 
 use core::ffi::c_void;
-use log::LevelFilter;
 
 use zephyr::device::DeviceSyscalls;
 use zephyr::mutex::*;
@@ -33,7 +31,7 @@ fn mutex_test() {
     // Should allow cloning directly if the data is a reference.
     let _other_mutex = mutex.clone();
     zephyr::any::k_str_out("Locking\n");
-    let _val = mutex.lock::<zephyr::context::Any>();
+    let _val = mutex.lock::<zephyr::context::Any>().unwrap();
     zephyr::any::k_str_out("Unlocking\n");
 }
 
@@ -71,8 +69,8 @@ pub extern "C" fn rust_second_thread(
     TLS_SEM.give::<zephyr::context::Kernel>();
 }
 
-#[no_mangle]
-pub extern "C" fn rust_main() {
+#[zephyr::main]
+fn main() {
     use zephyr::context::Kernel as Context;
 
     println!("Hello from Rust on Zephyr {} via println!", zephyr::KERNEL_VERSION);
@@ -112,8 +110,8 @@ pub extern "C" fn rust_main() {
         for _ in &a[..=(len - 1)] {}
     }
 
-    TLS_SEM.take::<Context>();
-    assert!(!TLS_SEM.try_take::<Context>());
+    TLS_SEM.take::<Context>().unwrap();
+    assert!(TLS_SEM.try_take::<Context>().is_err());
     TLS.with(|f| {
         println!("main thread: f = {}", *f.borrow());
         assert!(*f.borrow() == 1);
@@ -132,15 +130,13 @@ pub extern "C" fn rust_main() {
         mutex_test();
         std_mutex_test();
 
-        zephyr_logger::init(LevelFilter::Info);
-
         trace!("TEST: trace!()");
         debug!("TEST: debug!()");
         info!("TEST: info!()");
         warn!("TEST: warn!()");
         error!("TEST: error!()");
 
-        assert!(TLS_SEM.try_take::<Context>());
+        assert!(TLS_SEM.try_take::<Context>().is_ok());
         TLS.with(|f| {
             println!("main thread: f = {}", *f.borrow());
             assert!(*f.borrow() == 2);
@@ -0,0 +1,48 @@
+extern crate libc;
+extern crate zephyr;
+extern crate zephyr_macros;
+
+use libc::c_void;
+use std::collections::VecDeque;
+use std::time::Duration;
+use zephyr::condvar::Condvar;
+use zephyr::context::Kernel as C;
+use zephyr::mutex::Mutex;
+
+zephyr_macros::k_mutex_define!(QUEUE_MUTEX);
+zephyr_macros::k_condvar_define!(QUEUE_CONDVAR);
+
+static QUEUE: Mutex<'static, Option<VecDeque<u32>>> =
+    unsafe { Mutex::new(QUEUE_MUTEX.kobj(), None) };
+
+#[no_mangle]
+pub extern "C" fn rust_condvar_thread(_a: *const c_void, _b: *const c_void, _c: *const c_void) {
+    let mut queue = QUEUE.lock::<C>().unwrap();
+    queue = QUEUE_CONDVAR.wait_while(queue, |q| q.is_none()).unwrap();
+    for i in 0..10 {
+        println!("Pushing {}", i);
+        queue.as_mut().unwrap().push_back(i);
+        QUEUE_CONDVAR.notify_all::<C>();
+        queue = QUEUE_CONDVAR.wait_while(queue, |q| !q.as_ref().unwrap().is_empty()).unwrap();
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn rust_test_main() {
+    let mut queue = QUEUE.lock::<C>().unwrap();
+    let (q, result) = QUEUE_CONDVAR
+        .wait_timeout(queue, (&Duration::from_millis(10)).into())
+        .unwrap();
+    assert!(result.timed_out());
+    queue = q;
+
+    *queue = Some(VecDeque::new());
+    QUEUE_CONDVAR.notify_one::<C>();
+    for i in 0..10 {
+        queue = QUEUE_CONDVAR.wait_while(queue, |q| q.as_ref().unwrap().is_empty()).unwrap();
+        let val = queue.as_mut().unwrap().pop_front();
+        println!("Got {:?}", val);
+        assert_eq!(val, Some(i));
+        QUEUE_CONDVAR.notify_all::<C>();
+    }
+}
@@ -0,0 +1,55 @@
+extern crate libc;
+extern crate zephyr;
+extern crate zephyr_macros;
+
+use libc::c_void;
+use std::time::Duration;
+use zephyr::context::Kernel as C;
+use zephyr::event::Event;
+
+zephyr::event_flags! {
+    struct TestEvents {
+        const STARTED = 1 << 0;
+        const DATA = 1 << 1;
+        const DONE = 1 << 2;
+    }
+}
+
+zephyr_macros::k_event_define!(TEST_EVENT);
+
+#[no_mangle]
+pub extern "C" fn rust_event_thread(_a: *const c_void, _b: *const c_void, _c: *const c_void) {
+    TEST_EVENT.post::<C, _>(TestEvents::STARTED);
+    zephyr::kernel::k_sleep((&Duration::from_millis(10)).into());
+    TEST_EVENT.post::<C, _>(TestEvents::DATA | TestEvents::DONE);
+}
+
+#[no_mangle]
+pub extern "C" fn rust_test_main() {
+    let started = TEST_EVENT
+        .wait_any::<C, _>(TestEvents::all(), false, zephyr::K_FOREVER)
+        .expect("wait started");
+    println!("Got {:?}", started);
+    assert!(started.contains(TestEvents::STARTED));
+
+    let done = TEST_EVENT
+        .wait_all::<C, _>(
+            TestEvents::DATA | TestEvents::DONE,
+            false,
+            zephyr::K_FOREVER,
+        )
+        .expect("wait done");
+    println!("Got {:?}", done);
+    assert!(done.contains(TestEvents::DATA | TestEvents::DONE));
+
+    TEST_EVENT.clear::<C, _>(TestEvents::DATA);
+    assert_eq!(
+        TEST_EVENT.wait_any::<C, _>(TestEvents::DATA, false, zephyr::K_NO_WAIT),
+        None
+    );
+    TEST_EVENT.set::<C, _>(TestEvents::empty());
+    assert_eq!(
+        TEST_EVENT.wait_any::<C, _>(TestEvents::all(), false, zephyr::K_NO_WAIT),
+        None
+    );
+}
@@ -0,0 +1,49 @@
+extern crate libc;
+extern crate zephyr;
+extern crate zephyr_macros;
+
+use libc::c_void;
+use zephyr::context::Kernel as C;
+use zephyr::fifo::QueueBox;
+
+#[derive(Debug, PartialEq)]
+struct Message {
+    seq: u32,
+    payload: Vec<u8>,
+}
+
+zephyr_macros::k_fifo_define!(TEST_FIFO, Message);
+zephyr_macros::k_lifo_define!(TEST_LIFO, u32);
+
+#[no_mangle]
+pub extern "C" fn rust_fifo_thread(_a: *const c_void, _b: *const c_void, _c: *const c_void) {
+    for i in 0..10 {
+        println!("Putting {}", i);
+        TEST_FIFO
+            .put::<C>(QueueBox::new(Message {
+                seq: i,
+                payload: vec![i as u8; i as usize],
+            }))
+            .unwrap();
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn rust_test_main() {
+    for i in 0..10 {
+        let msg = TEST_FIFO.get::<C>().expect("fifo get");
+        println!("Got {:?}", msg);
+        assert_eq!(msg.seq, i);
+        assert_eq!(msg.payload, vec![i as u8; i as usize]);
+    }
+    assert!(TEST_FIFO.try_get::<C>().is_none());
+    assert!(TEST_FIFO.is_empty::<C>());
+
+    for i in 0..3 {
+        TEST_LIFO.put::<C>(QueueBox::new(i)).unwrap();
+    }
+    for i in (0..3).rev() {
+        assert_eq!(TEST_LIFO.try_get::<C>().map(QueueBox::into_inner), Some(i));
+    }
+    assert_eq!(TEST_LIFO.get_timeout::<C>(zephyr::K_NO_WAIT), None);
+}
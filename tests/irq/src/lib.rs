@@ -0,0 +1,67 @@
+extern crate zephyr;
+extern crate zephyr_macros;
+
+use std::cell::Cell;
+use std::time::Duration;
+use zephyr::context::Kernel as C;
+use zephyr::irq::IrqLock;
+use zephyr::spinlock::SpinMutex;
+use zephyr::timer::Timer;
+
+static SPIN_COUNT: SpinMutex<u32> = SpinMutex::new(0);
+static CS_COUNT: critical_section::Mutex<Cell<u32>> = critical_section::Mutex::new(Cell::new(0));
+
+// Runs in the timer ISR
+fn expiry() {
+    *SPIN_COUNT.lock::<C>() += 1;
+    critical_section::with(|cs| {
+        let count = CS_COUNT.borrow(cs);
+        count.set(count.get() + 1);
+    });
+}
+
+zephyr_macros::k_timer_define!(TIMER, expiry);
+
+extern "C" {
+    fn rust_test_busy_wait(usec: u32);
+}
+
+// k_busy_wait, since uptime does not advance while code runs on native_posix
+fn spin_for(duration: Duration) {
+    unsafe { rust_test_busy_wait(duration.as_micros() as u32) };
+}
+
+#[no_mangle]
+pub extern "C" fn rust_test_main() {
+    let period = Duration::from_millis(1);
+
+    // The timer cannot fire while interrupts are locked
+    {
+        let _lock = IrqLock::new::<C>();
+        let _nested = IrqLock::new::<C>();
+        TIMER.start::<C>((&period).into(), zephyr::K_NO_WAIT);
+        spin_for(Duration::from_millis(5));
+        assert_eq!(*SPIN_COUNT.lock::<C>(), 0);
+    }
+    assert_eq!(TIMER.status_sync::<C>(), 1);
+    assert_eq!(*SPIN_COUNT.lock::<C>(), 1);
+
+    // Nor while the spinlock is held
+    {
+        let mut count = SPIN_COUNT.lock::<C>();
+        TIMER.start::<C>((&period).into(), zephyr::K_NO_WAIT);
+        spin_for(Duration::from_millis(5));
+        *count += 10;
+    }
+    assert_eq!(TIMER.status_sync::<C>(), 1);
+    assert_eq!(*SPIN_COUNT.lock::<C>(), 12);
+
+    // Nor inside a critical section
+    critical_section::with(|cs| {
+        TIMER.start::<C>((&period).into(), zephyr::K_NO_WAIT);
+        spin_for(Duration::from_millis(5));
+        assert_eq!(CS_COUNT.borrow(cs).get(), 2);
+    });
+    assert_eq!(TIMER.status_sync::<C>(), 1);
+    assert_eq!(critical_section::with(|cs| CS_COUNT.borrow(cs).get()), 3);
+}
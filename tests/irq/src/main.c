@@ -0,0 +1,18 @@
+#include <version.h>
+#if KERNEL_VERSION_MAJOR < 3
+#include <zephyr.h>
+#else
+#include <zephyr/kernel.h>
+#endif
+
+extern void rust_test_main(void);
+
+void rust_test_busy_wait(uint32_t usec)
+{
+    k_busy_wait(usec);
+}
+
+void test_main(void)
+{
+    rust_test_main();
+}
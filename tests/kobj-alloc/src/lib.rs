@@ -0,0 +1,54 @@
+extern crate zephyr;
+extern crate zephyr_macros;
+
+use core::ffi::c_void;
+use zephyr::kobj_alloc::DynKObj;
+use zephyr::msgq::{KMsgq, MsgQueue};
+use zephyr::pipe::{KPipe, Pipe};
+use zephyr::poll::{KPollSignal, Signal};
+use zephyr::semaphore::{KSem, Semaphore};
+use zephyr::Error;
+
+fn exercise<C>()
+where
+    C: zephyr::kobj_alloc::KObjAllocSyscalls
+        + zephyr::semaphore::SemaphoreSyscalls
+        + zephyr::msgq::MsgqSyscalls
+        + zephyr::pipe::PipeSyscalls
+        + zephyr::poll::KPollSignalSyscalls,
+{
+    let sem = DynKObj::<KSem>::new::<C>(1, 2).unwrap();
+    sem.try_take::<C>().unwrap();
+    assert_eq!(sem.try_take::<C>(), Err(Error::Busy));
+    sem.give::<C>();
+    assert_eq!(sem.count::<C>(), 1);
+
+    let msgq = DynKObj::<KMsgq>::new::<C, u32>(2).unwrap();
+    let queue = unsafe { MsgQueue::<u32>::new(&msgq) };
+    queue.try_put::<C>(&1).unwrap();
+    queue.try_put::<C>(&2).unwrap();
+    assert_eq!(queue.try_put::<C>(&3), Err(Error::NoMessage));
+    assert_eq!(queue.get::<C>(), Ok(1));
+    assert_eq!(queue.get::<C>(), Ok(2));
+
+    let pipe = DynKObj::<KPipe>::new::<C>(16).unwrap();
+    assert_eq!(pipe.write::<C>(b"hello"), Ok(5));
+    let mut buf = [0u8; 8];
+    assert_eq!(pipe.read::<C>(&mut buf), Ok(5));
+    assert_eq!(&buf[..5], b"hello");
+
+    let signal = DynKObj::<KPollSignal>::new::<C>().unwrap();
+    assert_eq!(signal.check::<C>(), None);
+    signal.raise::<C>(7);
+    assert_eq!(signal.check::<C>(), Some(7));
+}
+
+#[no_mangle]
+pub extern "C" fn rust_test_main() {
+    exercise::<zephyr::context::Kernel>();
+}
+
+#[no_mangle]
+pub extern "C" fn rust_user_thread(_a: *const c_void, _b: *const c_void, _c: *const c_void) {
+    exercise::<zephyr::context::User>();
+}
@@ -0,0 +1,25 @@
+#include <version.h>
+#if KERNEL_VERSION_MAJOR < 3
+#include <zephyr.h>
+#else
+#include <zephyr/kernel.h>
+#endif
+
+extern void rust_test_main(void);
+extern void rust_user_thread(void *, void *, void *);
+extern struct k_mem_domain rust_std_domain;
+
+K_THREAD_DEFINE(user_thread, 2048, rust_user_thread, NULL, NULL, NULL,
+                0, K_USER, -1);
+
+void test_main(void)
+{
+    /* Dynamic objects and their buffers come from the thread's resource pool */
+    k_thread_system_pool_assign(k_current_get());
+    rust_test_main();
+
+    k_thread_system_pool_assign(user_thread);
+    k_mem_domain_add_thread(&rust_std_domain, user_thread);
+    k_thread_start(user_thread);
+    k_thread_join(user_thread, K_FOREVER);
+}
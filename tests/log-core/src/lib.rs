@@ -0,0 +1,37 @@
+extern crate zephyr;
+
+use log::LevelFilter;
+use zephyr_logger::ZephyrLogger;
+
+extern "C" {
+    fn rust_test_log_buffered() -> u32;
+}
+
+static LOGGER: ZephyrLogger =
+    ZephyrLogger::log_core().with_sources(&[("net", "rust_test_net"), ("missing", "missing")]);
+
+/// Records waiting in the log core, which is not processing them
+fn buffered() -> u32 {
+    unsafe { rust_test_log_buffered() }
+}
+
+#[no_mangle]
+pub extern "C" fn rust_test_main() {
+    // Limited by CONFIG_RUST_LOG_LEVEL
+    LOGGER.init(LevelFilter::Trace);
+    assert_eq!(log::max_level(), LevelFilter::Warn);
+
+    let start = buffered();
+    log::error!("error from Rust");
+    log::warn!("warning from Rust");
+    log::info!("filtered info from Rust");
+    assert_eq!(buffered(), start + 2);
+
+    // Logged by rust_test_net
+    log::warn!(target: "net", "warning from a mapped target");
+    log::warn!(target: "net::tcp", "warning from a submodule of a mapped target");
+    // Logged by the rust module
+    log::warn!(target: "missing", "warning from a target mapped to a missing module");
+    log::warn!(target: "rust_test", "warning with a target");
+    assert_eq!(buffered(), start + 6);
+}
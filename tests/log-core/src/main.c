@@ -0,0 +1,25 @@
+#include <version.h>
+#if KERNEL_VERSION_MAJOR < 3
+#include <zephyr.h>
+#include <logging/log.h>
+#include <logging/log_ctrl.h>
+#else
+#include <zephyr/kernel.h>
+#include <zephyr/logging/log.h>
+#include <zephyr/logging/log_ctrl.h>
+#endif
+
+extern void rust_test_main(void);
+
+/* Log module the Rust test maps a target to */
+LOG_MODULE_REGISTER(rust_test_net, LOG_LEVEL_DBG);
+
+uint32_t rust_test_log_buffered(void)
+{
+    return log_buffered_cnt();
+}
+
+void test_main(void)
+{
+    rust_test_main();
+}
@@ -0,0 +1,49 @@
+#![no_std]
+
+use log::{log_enabled, Level, LevelFilter};
+use zephyr_logger::ZephyrLogger;
+
+static LOGGER: ZephyrLogger = ZephyrLogger::console().with_targets(&[
+    ("app::quiet", LevelFilter::Error),
+    ("app::verbose", LevelFilter::Trace),
+]);
+
+mod quiet {
+    pub fn log() {
+        log::warn!("filtered warning");
+        log::error!("error from app::quiet");
+    }
+}
+
+extern "C" {
+    fn abort() -> !;
+}
+
+// Nothing links std, which would otherwise provide the panic handler
+#[panic_handler]
+fn panic(info: &core::panic::PanicInfo) -> ! {
+    log::error!("{}", info);
+    unsafe { abort() }
+}
+
+#[no_mangle]
+pub extern "C" fn rust_test_main() {
+    // Nothing limits the level without CONFIG_LOG
+    assert_eq!(zephyr_logger::STATIC_MAX_LEVEL, LevelFilter::Trace);
+
+    LOGGER.init(LevelFilter::Info);
+    // Raised for the verbose target
+    assert_eq!(log::max_level(), LevelFilter::Trace);
+
+    assert!(log_enabled!(Level::Info));
+    assert!(!log_enabled!(Level::Debug));
+    assert!(!log_enabled!(target: "app::quiet", Level::Warn));
+    assert!(!log_enabled!(target: "app::quiet::inner", Level::Warn));
+    assert!(log_enabled!(target: "app::quieter", Level::Warn));
+    assert!(log_enabled!(target: "app::verbose", Level::Trace));
+
+    log::info!("info from Rust without std");
+    quiet::log();
+    // Longer than the buffer, so printed in pieces
+    log::info!("{:=>200}", " long line");
+}
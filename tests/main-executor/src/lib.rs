@@ -0,0 +1,13 @@
+extern crate zephyr;
+extern crate zephyr_futures;
+
+use std::time::{Duration, Instant};
+
+use zephyr_futures::delay::Delay;
+
+#[zephyr::main(executor)]
+async fn main() {
+    let start = Instant::now();
+    Delay::new(Duration::from_millis(10)).await;
+    assert!(start.elapsed() >= Duration::from_millis(10));
+}
@@ -0,0 +1,13 @@
+#include <version.h>
+#if KERNEL_VERSION_MAJOR < 3
+#include <zephyr.h>
+#else
+#include <zephyr/kernel.h>
+#endif
+
+extern void rust_main(void);
+
+void test_main(void)
+{
+    rust_main();
+}
@@ -0,0 +1,14 @@
+extern crate zephyr;
+
+extern "C" {
+    fn rust_test_is_user() -> bool;
+}
+
+#[zephyr::main(user)]
+fn main() {
+    assert!(unsafe { rust_test_is_user() });
+    // The heap is usable, as main's thread was added to the Rust memory domain
+    let boxed = Box::new(1u8);
+    assert_eq!(*boxed, 1);
+    zephyr::user::k_str_out("Hello from Rust user mode main\n");
+}
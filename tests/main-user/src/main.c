@@ -0,0 +1,18 @@
+#include <version.h>
+#if KERNEL_VERSION_MAJOR < 3
+#include <zephyr.h>
+#else
+#include <zephyr/kernel.h>
+#endif
+
+extern void rust_main(void);
+
+bool rust_test_is_user(void)
+{
+    return k_is_user_context();
+}
+
+void test_main(void)
+{
+    rust_main();
+}
@@ -0,0 +1,78 @@
+extern crate zephyr;
+extern crate zephyr_macros;
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use zephyr::context::Kernel as C;
+use zephyr::memdomain::{MemDomain, UserAccess};
+use zephyr::thread::{options, Builder};
+use zephyr_macros::{app_bmem, app_dmem};
+
+zephyr_macros::k_mem_domain_define!(DOMAIN);
+zephyr_macros::k_mem_partition_define!(SHARED, 4096, UserAccess::ReadWrite);
+
+#[app_dmem(rust_app_part)]
+static INITIALIZED: AtomicU32 = AtomicU32::new(5);
+#[app_bmem(rust_app_part)]
+static ZEROED: AtomicU32 = AtomicU32::new(0);
+
+extern "C" {
+    static user_stack: u8;
+    static user_stack_size: usize;
+}
+
+#[no_mangle]
+pub extern "C" fn rust_test_main() {
+    assert_eq!(SHARED.size(), 4096);
+    assert_eq!(SHARED.start() as usize % 4096, 0);
+    let shared = SHARED.start() as *mut u32;
+    unsafe {
+        assert_eq!(shared.read_volatile(), 0);
+        shared.write_volatile(1);
+    }
+
+    let app_part = zephyr::static_mem_partition!(rust_app_part);
+    let in_app_part = |addr: usize| {
+        addr >= app_part.start() as usize && addr < app_part.start() as usize + app_part.size()
+    };
+    assert!(in_app_part(&INITIALIZED as *const _ as usize));
+    assert!(in_app_part(&ZEROED as *const _ as usize));
+    assert_eq!(INITIALIZED.load(Ordering::SeqCst), 5);
+    assert_eq!(ZEROED.load(Ordering::SeqCst), 0);
+
+    // Rust user threads need the Rust partition for their globals and heap
+    DOMAIN
+        .add_partition::<C>(zephyr::static_mem_partition!(rust_std_partition))
+        .unwrap();
+    DOMAIN.add_partition::<C>(&SHARED).unwrap();
+    DOMAIN.add_partition::<C>(app_part).unwrap();
+
+    // The partition struct is kernel data, so the user thread is given the address instead
+    let shared_addr = shared as usize;
+    let handle =
+        unsafe { Builder::new().raw_stack(&user_stack as *const u8 as *mut _, user_stack_size) }
+            .options(options::K_USER)
+            .delay(zephyr::K_FOREVER)
+            .spawn(move || unsafe {
+                let shared = shared_addr as *mut u32;
+                let value = shared.read_volatile();
+                shared.write_volatile(value + 1);
+                INITIALIZED.fetch_add(1, Ordering::SeqCst);
+                ZEROED.fetch_add(1, Ordering::SeqCst);
+                value
+            });
+    let tid = handle.thread();
+
+    // Moving the thread out and back in leaves it in the domain
+    DOMAIN.add_thread::<C>(tid);
+    MemDomain::remove_thread::<C>(tid);
+    DOMAIN.add_thread::<C>(tid);
+
+    tid.k_thread_start::<C>();
+    assert_eq!(handle.join(), Ok(1));
+    assert_eq!(unsafe { shared.read_volatile() }, 2);
+    assert_eq!(INITIALIZED.load(Ordering::SeqCst), 6);
+    assert_eq!(ZEROED.load(Ordering::SeqCst), 1);
+
+    DOMAIN.remove_partition::<C>(&SHARED).unwrap();
+    DOMAIN.add_partition::<C>(&SHARED).unwrap();
+}
@@ -0,0 +1,24 @@
+#include <version.h>
+#if KERNEL_VERSION_MAJOR < 3
+#include <zephyr.h>
+#else
+#include <zephyr/kernel.h>
+#endif
+#if KERNEL_VERSION_MAJOR < 3
+#include <app_memory/app_memdomain.h>
+#else
+#include <zephyr/app_memory/app_memdomain.h>
+#endif
+
+extern void rust_test_main(void);
+
+/* Holds the Rust statics placed with #[app_dmem] and #[app_bmem] */
+K_APPMEM_PARTITION_DEFINE(rust_app_part);
+
+K_THREAD_STACK_DEFINE(user_stack, 2048);
+const size_t user_stack_size = K_THREAD_STACK_SIZEOF(user_stack);
+
+void test_main(void)
+{
+    rust_test_main();
+}
@@ -0,0 +1,84 @@
+extern crate zephyr;
+extern crate zephyr_macros;
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+use zephyr::memslab::SlabBox;
+use zephyr::Error;
+
+static DROPPED: AtomicU32 = AtomicU32::new(0);
+
+#[derive(Debug, PartialEq)]
+struct Message {
+    id: u32,
+    payload: [u8; 12],
+}
+
+impl Drop for Message {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+zephyr_macros::k_mem_slab_define!(MESSAGES, Message, 2);
+zephyr_macros::k_mem_slab_define!(BYTES, u8, 4);
+
+#[no_mangle]
+pub extern "C" fn rust_test_main() {
+    assert_eq!(MESSAGES.num_used(), 0);
+    assert_eq!(MESSAGES.num_free(), 2);
+
+    let mut a = MESSAGES
+        .try_alloc(Message {
+            id: 1,
+            payload: [0; 12],
+        })
+        .unwrap();
+    let b = MESSAGES
+        .alloc(
+            Message {
+                id: 2,
+                payload: [2; 12],
+            },
+            zephyr::K_FOREVER,
+        )
+        .unwrap();
+    a.payload[0] = 1;
+    assert_eq!(a.id, 1);
+    assert_eq!(a.payload[0], 1);
+    assert_eq!(b.payload, [2; 12]);
+    assert_eq!(MESSAGES.num_used(), 2);
+    assert_eq!(MESSAGES.num_free(), 0);
+
+    // Full: the value is handed back
+    let c = Message {
+        id: 3,
+        payload: [3; 12],
+    };
+    let (err, c) = MESSAGES.try_alloc(c).unwrap_err();
+    assert_eq!(err, Error::NoMemory);
+    let (err, c) = MESSAGES
+        .alloc(c, (&Duration::from_millis(10)).into())
+        .unwrap_err();
+    assert_eq!(err, Error::Again);
+    assert_eq!(c.id, 3);
+    drop(c);
+    assert_eq!(DROPPED.swap(0, Ordering::SeqCst), 1);
+
+    // Dropping the box drops the value and frees the block
+    drop(a);
+    assert_eq!(DROPPED.swap(0, Ordering::SeqCst), 1);
+    assert_eq!(MESSAGES.num_free(), 1);
+
+    let b = SlabBox::into_inner(b);
+    assert_eq!(MESSAGES.num_free(), 2);
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 0);
+    assert_eq!(b.id, 2);
+
+    // Blocks of small types are padded to hold the kernel's free list link
+    let bytes: Vec<_> = (0..4).map(|i| BYTES.try_alloc(i).unwrap()).collect();
+    assert!(BYTES.try_alloc(4).is_err());
+    for (i, byte) in bytes.iter().enumerate() {
+        assert_eq!(**byte as usize, i);
+    }
+}
@@ -0,0 +1,42 @@
+extern crate libc;
+extern crate zephyr;
+extern crate zephyr_macros;
+
+use libc::c_void;
+use zephyr::context::Kernel as C;
+use zephyr::Error;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Message {
+    seq: u32,
+    tag: u8,
+}
+
+zephyr_macros::k_msgq_define!(TEST_MSGQ, Message, 4);
+
+#[no_mangle]
+pub extern "C" fn rust_msgq_thread(_a: *const c_void, _b: *const c_void, _c: *const c_void) {
+    for i in 0..10 {
+        println!("Putting {}", i);
+        TEST_MSGQ.put::<C>(&Message { seq: i, tag: 0xa5 }).unwrap();
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn rust_test_main() {
+    for i in 0..10 {
+        let msg = TEST_MSGQ.get::<C>().unwrap();
+        println!("Got {:?}", msg);
+        assert_eq!(msg, Message { seq: i, tag: 0xa5 });
+    }
+    assert_eq!(TEST_MSGQ.try_get::<C>(), Err(Error::NoMessage));
+    assert_eq!(TEST_MSGQ.num_used::<C>(), 0);
+
+    TEST_MSGQ
+        .try_put::<C>(&Message { seq: 42, tag: 0 })
+        .unwrap();
+    assert_eq!(TEST_MSGQ.peek::<C>(), Ok(Message { seq: 42, tag: 0 }));
+    assert_eq!(TEST_MSGQ.num_used::<C>(), 1);
+    TEST_MSGQ.purge::<C>();
+    assert_eq!(TEST_MSGQ.num_free::<C>(), 4);
+}
@@ -0,0 +1,50 @@
+extern crate zephyr;
+extern crate zephyr_macros;
+
+use std::time::Duration;
+use zephyr::context::Kernel as C;
+use zephyr::mutex::{Mutex, MutexGuard, RawMutex};
+use zephyr::thread::Builder;
+use zephyr::Error;
+
+zephyr_macros::k_mutex_define!(DATA_MUTEX);
+
+static DATA: Mutex<'static, u32> = unsafe { Mutex::new(DATA_MUTEX.kobj(), 0) };
+
+#[no_mangle]
+pub extern "C" fn rust_test_main() {
+    let mut guard = DATA.try_lock::<C>().unwrap();
+    *guard += 1;
+
+    // Held by this thread, so other threads can't lock it
+    let handle = Builder::new().stack_size(1024).spawn(|| {
+        assert!(DATA.try_lock::<C>().is_none());
+        assert_eq!(
+            DATA.lock_timeout::<C>(zephyr::K_NO_WAIT).err(),
+            Some(Error::Busy)
+        );
+        assert_eq!(
+            DATA.lock_timeout::<C>((&Duration::from_millis(10)).into())
+                .err(),
+            Some(Error::Again)
+        );
+    });
+    handle.join().unwrap();
+    assert_eq!(MutexGuard::unlock(guard), Ok(()));
+
+    let guard = DATA
+        .lock_timeout::<C>((&Duration::from_millis(10)).into())
+        .unwrap();
+    assert_eq!(*guard, 1);
+
+    // Only the thread that locked it can unlock it
+    let handle = Builder::new().stack_size(1024).spawn(|| unsafe {
+        assert_eq!(DATA_MUTEX.kobj().try_lock::<C>(), Err(Error::Busy));
+        DATA_MUTEX.kobj().unlock::<C>()
+    });
+    assert_eq!(handle.join(), Ok(Err(Error::NotPermitted)));
+    assert_eq!(MutexGuard::unlock(guard), Ok(()));
+    unsafe {
+        assert_eq!(DATA_MUTEX.kobj().unlock::<C>(), Err(Error::InvalidArgument));
+    }
+}
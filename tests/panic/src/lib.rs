@@ -0,0 +1,49 @@
+extern crate zephyr;
+
+use std::panic::PanicInfo;
+use std::sync::atomic::{AtomicU32, Ordering};
+use zephyr::context::Kernel as C;
+use zephyr::panic::Fatal;
+use zephyr::thread::Builder;
+
+extern "C" {
+    fn rust_test_fatal_count() -> u32;
+    fn rust_test_fatal_was_oops() -> bool;
+}
+
+static HOOK_LINE: AtomicU32 = AtomicU32::new(0);
+
+fn hook(info: &PanicInfo) {
+    HOOK_LINE.store(info.location().unwrap().line(), Ordering::SeqCst);
+}
+
+fn other_hook(_info: &PanicInfo) {}
+
+#[no_mangle]
+pub extern "C" fn rust_test_main() {
+    zephyr::panic::install();
+    zephyr::panic::set_hook(other_hook);
+    assert!(zephyr::panic::take_hook().is_some());
+    assert!(zephyr::panic::take_hook().is_none());
+    zephyr::panic::set_hook(hook);
+    zephyr::panic::set_fatal(Fatal::Oops);
+
+    // The panic is reported, then the thread is aborted by the oops
+    let panic_line = line!() + 5;
+    let handle = Builder::new()
+        .stack_size(2048)
+        .name("rust_panic_thread")
+        .spawn(|| {
+            panic!("test panic {}", 42);
+        });
+    handle
+        .thread()
+        .k_thread_join::<C>(zephyr::K_FOREVER)
+        .unwrap();
+    // The thread did not return, so leave its resources to the kernel
+    drop(handle);
+
+    assert_eq!(HOOK_LINE.load(Ordering::SeqCst), panic_line);
+    assert_eq!(unsafe { rust_test_fatal_count() }, 1);
+    assert!(unsafe { rust_test_fatal_was_oops() });
+}
@@ -0,0 +1,38 @@
+#include <version.h>
+#if KERNEL_VERSION_MAJOR < 3
+#include <zephyr.h>
+#else
+#include <zephyr/kernel.h>
+#endif
+
+extern void rust_test_main(void);
+
+static unsigned int fatal_count;
+static unsigned int fatal_reason;
+
+/* Return instead of halting, so the kernel only aborts the panicking thread */
+#if ZEPHYR_VERSION_CODE >= ZEPHYR_VERSION(3, 7, 0)
+void k_sys_fatal_error_handler(unsigned int reason, const struct arch_esf *esf)
+#else
+void k_sys_fatal_error_handler(unsigned int reason, const z_arch_esf_t *esf)
+#endif
+{
+    ARG_UNUSED(esf);
+    fatal_count++;
+    fatal_reason = reason;
+}
+
+unsigned int rust_test_fatal_count(void)
+{
+    return fatal_count;
+}
+
+bool rust_test_fatal_was_oops(void)
+{
+    return fatal_reason == K_ERR_KERNEL_OOPS;
+}
+
+void test_main(void)
+{
+    rust_test_main();
+}
@@ -0,0 +1,62 @@
+extern crate libc;
+extern crate zephyr;
+extern crate zephyr_macros;
+
+use libc::c_void;
+use std::io::{Read, Write};
+use std::time::Duration;
+use zephyr::context::Kernel as C;
+use zephyr::pipe::{Pipe, PipeIo};
+use zephyr::semaphore::Semaphore;
+use zephyr::Error;
+
+zephyr_macros::k_pipe_define!(TEST_PIPE, 16);
+zephyr_macros::k_sem_define!(START_SEM, 0, 1);
+
+#[no_mangle]
+pub extern "C" fn rust_pipe_thread(_a: *const c_void, _b: *const c_void, _c: *const c_void) {
+    START_SEM.take::<C>().unwrap();
+    // More than the pipe holds, so this blocks until the reader catches up
+    let data: Vec<u8> = (0..100).collect();
+    PipeIo::<C>::new(&TEST_PIPE).write_all(&data).unwrap();
+}
+
+#[no_mangle]
+pub extern "C" fn rust_test_main() {
+    let timeout = (&Duration::from_millis(10)).into();
+    let mut buf = [0u8; 32];
+
+    // Nothing to read
+    assert_eq!(TEST_PIPE.read_nb::<C>(&mut buf), Err(Error::Io));
+    assert_eq!(
+        TEST_PIPE.read_timeout::<C>(&mut buf, timeout),
+        Err(Error::Again)
+    );
+
+    // A write that does not fit is partial, then the pipe is full
+    let data = [0xa5u8; 20];
+    assert_eq!(TEST_PIPE.write_nb::<C>(&data), Ok(16));
+    assert_eq!(TEST_PIPE.write_nb::<C>(&data), Err(Error::Io));
+    assert_eq!(
+        TEST_PIPE.write_timeout::<C>(&data, timeout),
+        Err(Error::Again)
+    );
+
+    // Reads return up to the buffer size, or what is there
+    assert_eq!(TEST_PIPE.read::<C>(&mut buf[..4]), Ok(4));
+    assert_eq!(TEST_PIPE.read_timeout::<C>(&mut buf, timeout), Ok(12));
+    assert!(buf[..12].iter().all(|&b| b == 0xa5));
+    assert_eq!(TEST_PIPE.read_nb::<C>(&mut buf), Err(Error::Io));
+
+    // Empty transfers do not wait
+    assert_eq!(TEST_PIPE.write::<C>(&[]), Ok(0));
+    assert_eq!(TEST_PIPE.read::<C>(&mut []), Ok(0));
+
+    // Stream through std::io from another thread
+    START_SEM.give::<C>();
+    let mut received = [0u8; 100];
+    PipeIo::<C>::new(&TEST_PIPE)
+        .read_exact(&mut received)
+        .unwrap();
+    assert!(received.iter().enumerate().all(|(i, &b)| b == i as u8));
+}
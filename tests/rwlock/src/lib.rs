@@ -0,0 +1,65 @@
+extern crate zephyr;
+extern crate zephyr_macros;
+
+use std::time::Duration;
+use zephyr::context::Kernel as C;
+use zephyr::rwlock::{RwLock, RwLockWriteGuard};
+use zephyr::thread::Builder;
+use zephyr::Error;
+
+zephyr_macros::k_rwlock_define!(CONFIG_LOCK);
+
+static CONFIG: RwLock<'static, u32> = unsafe { RwLock::new(&CONFIG_LOCK, 0) };
+
+#[no_mangle]
+pub extern "C" fn rust_test_main() {
+    // Any number of readers
+    let a = CONFIG.try_read::<C>().unwrap();
+    let b = CONFIG.read::<C>().unwrap();
+    assert_eq!(*a + *b, 0);
+    assert!(CONFIG.try_write::<C>().is_none());
+    assert_eq!(
+        CONFIG.write_timeout::<C>(zephyr::K_NO_WAIT).err(),
+        Some(Error::Busy)
+    );
+    assert_eq!(
+        CONFIG
+            .write_timeout::<C>((&Duration::from_millis(10)).into())
+            .err(),
+        Some(Error::Again)
+    );
+    drop(a);
+    drop(b);
+
+    // One writer, which can't take the lock again
+    let mut guard = CONFIG.write::<C>().unwrap();
+    *guard += 1;
+    assert!(CONFIG.try_read::<C>().is_none());
+    assert_eq!(
+        CONFIG.read_timeout::<C>(zephyr::K_NO_WAIT).err(),
+        Some(Error::Deadlock)
+    );
+    assert_eq!(CONFIG.write::<C>().err(), Some(Error::Deadlock));
+    let handle = Builder::new().stack_size(1024).spawn(|| {
+        assert_eq!(
+            CONFIG
+                .read_timeout::<C>((&Duration::from_millis(10)).into())
+                .err(),
+            Some(Error::Again)
+        );
+    });
+    handle.join().unwrap();
+    assert_eq!(RwLockWriteGuard::unlock(guard), Ok(()));
+
+    // A waiting writer keeps new readers out
+    let reader = CONFIG.read::<C>().unwrap();
+    let handle = Builder::new().stack_size(1024).spawn(|| {
+        *CONFIG.write::<C>().unwrap() += 1;
+    });
+    zephyr::kernel::k_sleep((&Duration::from_millis(10)).into());
+    assert!(CONFIG.try_read::<C>().is_none());
+    assert_eq!(*reader, 1);
+    drop(reader);
+    handle.join().unwrap();
+    assert_eq!(*CONFIG.read::<C>().unwrap(), 2);
+}
@@ -20,7 +20,7 @@ pub extern "C" fn rust_sem_thread(_a: *const c_void, _b: *const c_void, _c: *con
 pub extern "C" fn rust_test_main() {
     for i in 0..10 {
         println!("Taking {}", i);
-        TEST_SEM.take::<C>();
+        TEST_SEM.take::<C>().unwrap();
         println!("Took {}", i);
     }
 }
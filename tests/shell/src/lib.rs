@@ -0,0 +1,70 @@
+extern crate zephyr;
+
+use core::fmt::Write;
+use std::ffi::CString;
+use std::os::raw::{c_char, c_int};
+use std::sync::atomic::{AtomicI32, AtomicU32, Ordering};
+use zephyr::error::Result;
+use zephyr::shell::Shell;
+use zephyr::Error;
+
+extern "C" {
+    fn rust_test_shell_execute(cmd: *const c_char) -> c_int;
+}
+
+static PINGS: AtomicU32 = AtomicU32::new(0);
+static SUM: AtomicI32 = AtomicI32::new(0);
+
+fn ping(shell: &mut Shell, args: &[&str]) -> Result<()> {
+    // Registered both as rust_demo's subcommand and as the root command rust_ping
+    assert_eq!(args.len(), 1);
+    writeln!(shell, "pong").unwrap();
+    PINGS.fetch_add(1, Ordering::SeqCst);
+    Ok(())
+}
+
+fn sum(shell: &mut Shell, args: &[&str]) -> Result<()> {
+    let mut sum = 0;
+    for arg in &args[1..] {
+        sum += arg.parse::<i32>().map_err(|_| Error::InvalidArgument)?;
+    }
+    writeln!(shell, "{}", sum).unwrap();
+    SUM.store(sum, Ordering::SeqCst);
+    Ok(())
+}
+
+zephyr::shell_static_subcmd_set_create!(
+    SUB_RUST_DEMO,
+    zephyr::shell_cmd!(ping, None, "Reply with pong", ping),
+    zephyr::shell_cmd_arg!(sum, None, "Add integers", sum, 2, 8),
+);
+zephyr::shell_cmd_register!(rust_demo, SUB_RUST_DEMO, "Rust commands", None);
+zephyr::shell_cmd_arg_register!(rust_ping, None, "Reply with pong", ping, 1, 0);
+
+fn execute(cmd: &str) -> c_int {
+    let cmd = CString::new(cmd).unwrap();
+    unsafe { rust_test_shell_execute(cmd.as_ptr()) }
+}
+
+#[no_mangle]
+pub extern "C" fn rust_test_main() {
+    assert_eq!(execute("rust_demo ping"), 0);
+    assert_eq!(PINGS.load(Ordering::SeqCst), 1);
+
+    assert_eq!(execute("rust_ping"), 0);
+    assert_eq!(PINGS.load(Ordering::SeqCst), 2);
+    // The shell checks the argument count before calling the handler
+    assert_ne!(execute("rust_ping extra"), 0);
+    assert_eq!(PINGS.load(Ordering::SeqCst), 2);
+
+    assert_eq!(execute("rust_demo sum 1 2 3"), 0);
+    assert_eq!(SUM.load(Ordering::SeqCst), 6);
+
+    // Errors from the handler are returned as a negative errno
+    let einval = -(Error::InvalidArgument.errno() as c_int);
+    assert_eq!(execute("rust_demo sum 1 x"), einval);
+    assert_eq!(SUM.load(Ordering::SeqCst), 6);
+
+    // A subcommand set without a handler needs a subcommand
+    assert_ne!(execute("rust_demo"), 0);
+}
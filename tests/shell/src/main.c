@@ -0,0 +1,23 @@
+#include <version.h>
+#if KERNEL_VERSION_MAJOR < 3
+#include <zephyr.h>
+#include <shell/shell.h>
+#include <shell/shell_uart.h>
+#else
+#include <zephyr/kernel.h>
+#include <zephyr/shell/shell.h>
+#include <zephyr/shell/shell_uart.h>
+#endif
+
+extern void rust_test_main(void);
+
+/* Run a command on the console shell as if it were typed */
+int rust_test_shell_execute(const char *cmd)
+{
+    return shell_execute_cmd(shell_backend_uart_get_ptr(), cmd);
+}
+
+void test_main(void)
+{
+    rust_test_main();
+}
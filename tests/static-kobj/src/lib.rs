@@ -0,0 +1,52 @@
+extern crate zephyr;
+extern crate zephyr_macros;
+
+use zephyr::condvar::Condvar;
+use zephyr::context::Kernel as C;
+use zephyr::mutex::Mutex;
+use zephyr::poll::{KPollEvent, PollEventFuncs, PollEventsFuncs, PollMode, Signal};
+use zephyr::rwlock::RwLock;
+use zephyr::semaphore::Semaphore;
+use zephyr::Error;
+
+// No constructors run in this test. These are initialized at compile time.
+zephyr_macros::k_mutex_define!(MUTEX);
+zephyr_macros::k_sem_define!(SEM, 1, 2);
+zephyr_macros::k_poll_signal_define!(SIGNAL);
+zephyr_macros::k_condvar_define!(CONDVAR);
+zephyr_macros::k_rwlock_define!(RWLOCK);
+
+static DATA: Mutex<'static, u32> = unsafe { Mutex::new(MUTEX.kobj(), 0) };
+static CONFIG: RwLock<'static, u32> = unsafe { RwLock::new(&RWLOCK, 0) };
+
+#[no_mangle]
+pub extern "C" fn rust_test_main() {
+    *DATA.lock::<C>().unwrap() += 1;
+    assert_eq!(*DATA.try_lock::<C>().unwrap(), 1);
+
+    assert_eq!(SEM.count::<C>(), 1);
+    SEM.give::<C>();
+    SEM.give::<C>();
+    assert_eq!(SEM.count::<C>(), 2);
+    SEM.take::<C>().unwrap();
+    SEM.take::<C>().unwrap();
+    assert_eq!(SEM.try_take::<C>(), Err(Error::Busy));
+
+    assert_eq!(SIGNAL.check::<C>(), None);
+    SIGNAL.raise::<C>(7);
+    let mut events = [KPollEvent::new(), KPollEvent::new()];
+    events[0].init(SIGNAL.kobj(), PollMode::NotifyOnly);
+    events[1].init(SEM.kobj(), PollMode::NotifyOnly);
+    assert!(events.poll_timeout::<C>(Some(zephyr::K_NO_WAIT)).unwrap());
+    assert!(events[0].ready());
+    assert!(!events[1].ready());
+    assert_eq!(SIGNAL.check::<C>(), Some(7));
+
+    let guard = DATA.lock::<C>().unwrap();
+    let (guard, result) = CONDVAR.wait_timeout(guard, zephyr::K_NO_WAIT).unwrap();
+    assert!(result.timed_out());
+    drop(guard);
+
+    *CONFIG.write::<C>().unwrap() += 1;
+    assert_eq!(*CONFIG.read::<C>().unwrap(), 1);
+}
@@ -0,0 +1,54 @@
+extern crate zephyr;
+extern crate zephyr_macros;
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use zephyr::context::Kernel as C;
+use zephyr::semaphore::Semaphore;
+use zephyr::thread::ThreadSyscalls;
+
+zephyr_macros::k_sem_define!(BOOT_SEM, 0, 1);
+zephyr_macros::k_sem_define!(START_SEM, 0, 1);
+zephyr_macros::k_sem_define!(USER_SEM, 0, 1);
+
+static COUNT: AtomicU32 = AtomicU32::new(0);
+
+// Created and started by the kernel before the test runs
+#[zephyr::thread(stack = 1024, priority = 5)]
+fn boot_thread() {
+    COUNT.fetch_add(1, Ordering::SeqCst);
+    BOOT_SEM.give::<C>();
+}
+
+// Created at boot, but only runs once started
+#[zephyr::thread(stack = 1024, priority = 5, delay = forever)]
+fn start_thread() {
+    COUNT.fetch_add(1, Ordering::SeqCst);
+    START_SEM.give::<C>();
+}
+
+#[zephyr::thread(stack = 1024, priority = 5, c_stack = rust_user_stack, user, delay = forever)]
+fn user_thread() {
+    USER_SEM.give::<zephyr::context::User>();
+}
+
+#[no_mangle]
+pub extern "C" fn rust_test_main() {
+    BOOT_SEM.take::<C>().unwrap();
+    assert_eq!(COUNT.load(Ordering::SeqCst), 1);
+
+    let mut buf = [0u8; 32];
+    let name = BOOT_THREAD.tid().k_thread_name_copy::<C>(&mut buf);
+    assert_eq!(name, Ok("boot_thread"));
+
+    assert_eq!(START_THREAD.tid().k_thread_priority_get::<C>(), 5);
+    assert_eq!(COUNT.load(Ordering::SeqCst), 1);
+    START_THREAD.tid().k_thread_start::<C>();
+    START_SEM.take::<C>().unwrap();
+    assert_eq!(COUNT.load(Ordering::SeqCst), 2);
+
+    let user = USER_THREAD.tid();
+    user.k_object_access_grant::<C, _>(&USER_SEM);
+    user.k_thread_start::<C>();
+    USER_SEM.take::<C>().unwrap();
+    user.k_thread_join::<C>(zephyr::K_FOREVER).unwrap();
+}
@@ -0,0 +1,16 @@
+#include <version.h>
+#if KERNEL_VERSION_MAJOR < 3
+#include <zephyr.h>
+#else
+#include <zephyr/kernel.h>
+#endif
+
+/* User threads need a stack that can be granted to user mode */
+K_THREAD_STACK_DEFINE(rust_user_stack, 1024);
+
+extern void rust_test_main(void);
+
+void test_main(void)
+{
+    rust_test_main();
+}
@@ -0,0 +1,51 @@
+extern crate zephyr;
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use zephyr::error::Result;
+use zephyr::Error;
+
+// Bit per init function, in the order they ran
+static ORDER: AtomicU32 = AtomicU32::new(0);
+static RAN: AtomicU32 = AtomicU32::new(0);
+
+fn ran(bit: u32) {
+    let n = RAN.fetch_add(1, Ordering::SeqCst);
+    ORDER.fetch_or(bit << (n * 4), Ordering::SeqCst);
+}
+
+fn pre_kernel_1() -> Result<()> {
+    ran(1);
+    Ok(())
+}
+
+fn post_kernel_late() -> Result<()> {
+    ran(3);
+    Ok(())
+}
+
+fn post_kernel_early() -> Result<()> {
+    ran(2);
+    Ok(())
+}
+
+// A failing init function does not stop the boot
+fn application() -> Result<()> {
+    ran(4);
+    Err(Error::NoDevice)
+}
+
+zephyr::sys_init!(application, APPLICATION, 90);
+zephyr::sys_init!(post_kernel_late, POST_KERNEL, 60);
+zephyr::sys_init!(post_kernel_early, POST_KERNEL, 50);
+zephyr::sys_init!(pre_kernel_1, PRE_KERNEL_1, 50);
+
+#[no_mangle]
+pub extern "C" fn rust_test_main() {
+    assert_eq!(RAN.load(Ordering::SeqCst), 4);
+    assert_eq!(ORDER.load(Ordering::SeqCst), 0x4321);
+    assert_eq!(zephyr::init::return_code(Ok(())), 0);
+    assert_eq!(
+        zephyr::init::return_code(Err(Error::NoDevice)),
+        -(Error::NoDevice.errno() as i32)
+    );
+}
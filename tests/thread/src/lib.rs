@@ -0,0 +1,97 @@
+extern crate zephyr;
+extern crate zephyr_macros;
+
+use std::ffi::CStr;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use zephyr::context::Kernel as C;
+use zephyr::semaphore::Semaphore;
+use zephyr::thread::{options, Builder, ThreadSyscalls};
+use zephyr::Error;
+
+zephyr_macros::k_sem_define!(START_SEM, 0, 1);
+zephyr_macros::k_kernel_stack_define!(THREAD_STACK, 1024);
+
+#[no_mangle]
+pub extern "C" fn rust_test_main() {
+    let handle = Builder::new()
+        .stack_size(1024)
+        .name("rust_heap_thread")
+        .spawn(|| 6 * 7);
+    assert_ne!(handle.thread(), C::k_current_get());
+    assert_eq!(handle.join(), Ok(42));
+
+    // The closure owns what it captures and hands its result back
+    let count = Arc::new(AtomicU32::new(0));
+    let c = count.clone();
+    let handle = unsafe { Builder::new().stack(&THREAD_STACK) }
+        .priority(1)
+        .options(options::K_FP_REGS)
+        .spawn(move || {
+            START_SEM.take::<C>().unwrap();
+            c.fetch_add(1, Ordering::SeqCst);
+            vec![1u8, 2, 3]
+        });
+    assert_eq!(count.load(Ordering::SeqCst), 0);
+    START_SEM.give::<C>();
+    assert_eq!(handle.join(), Ok(vec![1, 2, 3]));
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+    assert_eq!(Arc::strong_count(&count), 1);
+
+    // A delayed start does not run before the delay
+    let c = count.clone();
+    let handle = Builder::new()
+        .stack_size(1024)
+        .delay((&Duration::from_millis(50)).into())
+        .spawn(move || c.fetch_add(1, Ordering::SeqCst));
+    zephyr::kernel::k_sleep((&Duration::from_millis(10)).into());
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+    assert_eq!(handle.join(), Ok(1));
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+
+    // Control a thread through its ThreadId
+    let c = count.clone();
+    let handle = Builder::new()
+        .stack_size(1024)
+        .priority(5)
+        .delay(zephyr::K_FOREVER)
+        .spawn(move || {
+            START_SEM.take::<C>().unwrap();
+            c.fetch_add(1, Ordering::SeqCst);
+        });
+    let tid = handle.thread();
+    assert_eq!(tid.k_thread_priority_get::<C>(), 5);
+    tid.k_thread_priority_set::<C>(6);
+    assert_eq!(tid.k_thread_priority_get::<C>(), 6);
+
+    let name = CStr::from_bytes_with_nul(b"rust_ctl\0").unwrap();
+    tid.k_thread_name_set::<C>(name).unwrap();
+    let mut buf = [0u8; 32];
+    assert_eq!(tid.k_thread_name_copy::<C>(&mut buf), Ok("rust_ctl"));
+
+    assert_eq!(tid.k_thread_join::<C>(zephyr::K_NO_WAIT), Err(Error::Busy));
+    assert_eq!(
+        C::k_current_get().k_thread_join::<C>(zephyr::K_FOREVER),
+        Err(Error::Deadlock)
+    );
+
+    tid.k_thread_start::<C>();
+    assert_eq!(
+        tid.k_thread_join::<C>((&Duration::from_millis(10)).into()),
+        Err(Error::Again)
+    );
+    START_SEM.give::<C>();
+    handle.join().unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 3);
+
+    // An aborted thread never produced a result, so joining its handle reports it as canceled
+    let handle = Builder::new().stack_size(1024).spawn(|| {
+        START_SEM.take::<C>().unwrap();
+    });
+    let tid = handle.thread();
+    tid.k_thread_suspend::<C>();
+    tid.k_thread_resume::<C>();
+    tid.k_thread_abort::<C>();
+    assert_eq!(handle.join(), Err(Error::Canceled));
+}
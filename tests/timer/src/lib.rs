@@ -0,0 +1,71 @@
+extern crate zephyr;
+extern crate zephyr_macros;
+
+use futures::stream::StreamExt;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+use zephyr::context::Kernel as C;
+use zephyr::poll::{KPollEvent, PollEventFuncs, PollEventsFuncs, PollMode, Signal};
+use zephyr::timer::Timer;
+use zephyr_futures::{Executor, TimerStream};
+
+static EXPIRED: AtomicU32 = AtomicU32::new(0);
+
+fn expiry() {
+    EXPIRED.fetch_add(1, Ordering::SeqCst);
+}
+
+zephyr_macros::k_timer_define!(PERIODIC_TIMER, expiry);
+zephyr_macros::k_timer_define!(ONESHOT_TIMER);
+zephyr_macros::k_timer_define!(SIGNAL_TIMER);
+zephyr_macros::k_poll_signal_define!(TIMER_SIGNAL);
+zephyr_macros::k_timer_define!(STREAM_TIMER);
+zephyr_macros::k_poll_signal_define!(STREAM_SIGNAL);
+zephyr_macros::k_mutex_define!(EXECUTOR_MUTEX);
+zephyr_macros::k_poll_signal_define!(EXECUTOR_SIGNAL);
+
+#[no_mangle]
+pub extern "C" fn rust_test_main() {
+    let period = Duration::from_millis(10);
+    PERIODIC_TIMER.start::<C>((&period).into(), (&period).into());
+    for _ in 0..3 {
+        assert!(PERIODIC_TIMER.status_sync::<C>() >= 1);
+    }
+    PERIODIC_TIMER.stop::<C>();
+    let expired = EXPIRED.load(Ordering::SeqCst);
+    println!("Periodic timer expired {} times", expired);
+    assert!(expired >= 3);
+    assert_eq!(PERIODIC_TIMER.status_sync::<C>(), 0);
+
+    ONESHOT_TIMER.start::<C>((&period).into(), zephyr::K_NO_WAIT);
+    assert_eq!(ONESHOT_TIMER.status_get::<C>(), 0);
+    assert_eq!(ONESHOT_TIMER.status_sync::<C>(), 1);
+    assert_eq!(ONESHOT_TIMER.status_get::<C>(), 0);
+
+    // Expiry raises the signal, which can be waited on with k_poll
+    unsafe { SIGNAL_TIMER.init_with_signal(&TIMER_SIGNAL) };
+    assert_eq!(TIMER_SIGNAL.check::<C>(), None);
+    SIGNAL_TIMER.start::<C>((&period).into(), zephyr::K_NO_WAIT);
+    let mut events = [KPollEvent::new()];
+    events[0].init(&**TIMER_SIGNAL, PollMode::NotifyOnly);
+    assert_eq!(
+        events.poll_timeout::<C>(Some((&Duration::from_secs(1)).into())),
+        Ok(true)
+    );
+    assert_eq!(TIMER_SIGNAL.check::<C>(), Some(0));
+    assert_eq!(SIGNAL_TIMER.status_get::<C>(), 1);
+
+    // The stream yields expiries until the executor stops polling it
+    unsafe { STREAM_TIMER.init_with_signal(&STREAM_SIGNAL) };
+    STREAM_TIMER.start::<C>((&period).into(), (&period).into());
+    let ticks = TimerStream::new(&STREAM_TIMER, &STREAM_SIGNAL)
+        .take(3)
+        .fold(0, |total, count| async move { total + count });
+    let mut executor = unsafe { Executor::new(&EXECUTOR_MUTEX, &EXECUTOR_SIGNAL) };
+    executor.block_on::<C, _>(async move {
+        let total = ticks.await;
+        println!("Timer stream counted {} expiries", total);
+        assert!(total >= 3);
+    });
+    STREAM_TIMER.stop::<C>();
+}
@@ -0,0 +1,75 @@
+extern crate zephyr;
+extern crate zephyr_macros;
+
+use std::ffi::CStr;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use zephyr::context::Kernel as C;
+use zephyr::semaphore::Semaphore;
+use zephyr::work::{DelayableWork, Work, WorkQueue};
+
+zephyr_macros::k_sem_define!(DONE_SEM, 0, 10);
+zephyr_macros::k_kernel_stack_define!(WORK_Q_STACK, 1024);
+
+#[no_mangle]
+pub extern "C" fn rust_test_main() {
+    let count = Arc::new(AtomicU32::new(0));
+
+    let work = Work::new();
+    let c = count.clone();
+    assert!(work
+        .submit(move || {
+            c.fetch_add(1, Ordering::SeqCst);
+            DONE_SEM.give::<C>();
+        })
+        .unwrap());
+    DONE_SEM.take::<C>().unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+
+    // Nothing else runs on the stack
+    let queue = unsafe {
+        WorkQueue::start(
+            &WORK_Q_STACK,
+            5,
+            Some(CStr::from_bytes_with_nul(b"rust_work_q\0").unwrap()),
+        )
+    };
+    let c = count.clone();
+    work.submit_to_queue(queue, move || {
+        c.fetch_add(1, Ordering::SeqCst);
+    })
+    .unwrap();
+    work.flush();
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+
+    let delayed = DelayableWork::new();
+    let c = count.clone();
+    assert!(delayed
+        .schedule(Duration::from_secs(10), move || {
+            c.fetch_add(100, Ordering::SeqCst);
+        })
+        .unwrap());
+    assert!(delayed.is_busy());
+    let c = count.clone();
+    delayed
+        .reschedule(Duration::from_millis(10), move || {
+            c.fetch_add(1, Ordering::SeqCst);
+            DONE_SEM.give::<C>();
+        })
+        .unwrap();
+    DONE_SEM.take::<C>().unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 3);
+
+    let c = count.clone();
+    delayed
+        .schedule(Duration::from_millis(10), move || {
+            c.fetch_add(100, Ordering::SeqCst);
+        })
+        .unwrap();
+    assert!(delayed.cancel_sync());
+    assert!(!delayed.is_busy());
+    assert_eq!(count.load(Ordering::SeqCst), 3);
+    // The cancelled closures were dropped
+    assert_eq!(Arc::strong_count(&count), 1);
+}